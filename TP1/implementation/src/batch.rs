@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Instant;
+
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+use crate::cli_args::{Algorithm, BatchArgs, ScalarType};
+use crate::matrix_element::{BinaryElement, MatrixElement};
+use crate::matrix_utils::load_matrix;
+use crate::modular_integer::{set_modulus, ModularInteger};
+use crate::multiplication_algorithms::multiply_matrices;
+
+const DEFAULT_THRESHOLD: usize = 16;
+
+#[derive(Deserialize)]
+struct ManifestJob {
+    matrix_1: PathBuf,
+    matrix_2: PathBuf,
+    algorithm: String,
+    threshold: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct JobResult {
+    matrix_1: PathBuf,
+    matrix_2: PathBuf,
+    algorithm: String,
+    threshold: usize,
+    n: usize,
+    execution_times_ms: Vec<f64>,
+    mean_execution_time_ms: f64,
+    min_execution_time_ms: f64,
+    checksum: String,
+}
+
+pub fn run_batch(args: &BatchArgs) {
+    let scalar_type = args.scalar_type();
+    let jobs = load_manifest(&args.manifest_filename).expect("Error parsing batch manifest");
+
+    let results = match scalar_type {
+        ScalarType::Integer => run_jobs::<i32>(&jobs, args.repeat_count),
+        ScalarType::Float => run_jobs::<f64>(&jobs, args.repeat_count),
+        ScalarType::Modular => {
+            set_modulus(args.modulus).expect("Invalid modulus");
+            run_jobs::<ModularInteger>(&jobs, args.repeat_count)
+        }
+    }
+    .expect("Error running batch jobs");
+
+    let writer = BufWriter::new(File::create(&args.output_filename).expect("Error creating batch results file"));
+    serde_json::to_writer_pretty(writer, &results).expect("Error writing batch results file");
+}
+
+/// Reads the jobs from a JSON or CSV manifest, resolving matrix paths relative to the manifest's directory
+fn load_manifest(filename: &Path) -> Result<Vec<ManifestJob>, Box<dyn Error>> {
+    let mut jobs: Vec<ManifestJob> = if filename.extension() == Some(OsStr::new("json")) {
+        serde_json::from_reader(File::open(filename)?)?
+    } else {
+        csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(filename)?
+            .deserialize()
+            .collect::<Result<_, _>>()?
+    };
+
+    let manifest_directory = filename.parent().unwrap_or_else(|| Path::new(""));
+    for job in &mut jobs {
+        job.matrix_1 = manifest_directory.join(&job.matrix_1);
+        job.matrix_2 = manifest_directory.join(&job.matrix_2);
+    }
+
+    Ok(jobs)
+}
+
+fn run_jobs<T>(jobs: &[ManifestJob], repeat_count: usize) -> Result<Vec<JobResult>, Box<dyn Error>>
+where
+    T: MatrixElement + BinaryElement + FromStr,
+    T::Err: Error + 'static,
+{
+    // Each matrix file is parsed only once, even if it is used by several jobs
+    let mut matrices: HashMap<PathBuf, Array2<T>> = HashMap::new();
+    for job in jobs {
+        for filename in [&job.matrix_1, &job.matrix_2] {
+            if !matrices.contains_key(filename) {
+                let matrix = load_matrix(filename).map_err(|e| format!("Error parsing matrix {}: {}", filename.display(), e))?;
+                matrices.insert(filename.clone(), matrix);
+            }
+        }
+    }
+
+    let mut results = Vec::new();
+    for job in jobs {
+        let algorithm: Algorithm = job.algorithm.parse()?;
+        let threshold = job.threshold.unwrap_or(DEFAULT_THRESHOLD);
+
+        let matrix_1 = &matrices[&job.matrix_1];
+        let matrix_2 = &matrices[&job.matrix_2];
+        if matrix_1.shape() != matrix_2.shape() {
+            return Err(format!("Incompatible shapes between matrices {} and {}", job.matrix_1.display(), job.matrix_2.display()).into());
+        }
+        if !matrix_1.is_square() || !matrix_1.nrows().is_power_of_two() {
+            return Err(format!(
+                "Matrices {} and {} must be square with a power of two size, got {}x{}",
+                job.matrix_1.display(),
+                job.matrix_2.display(),
+                matrix_1.nrows(),
+                matrix_1.ncols()
+            )
+            .into());
+        }
+
+        let mut execution_times_ms = Vec::new();
+        let mut result = None;
+        for _ in 0..repeat_count {
+            let now = Instant::now();
+            let product = multiply_matrices(algorithm, matrix_1, matrix_2, threshold);
+            execution_times_ms.push(now.elapsed().as_secs_f64() * 1000.0);
+            result = Some(product);
+        }
+
+        let mean_execution_time_ms = execution_times_ms.iter().sum::<f64>() / execution_times_ms.len() as f64;
+        let min_execution_time_ms = execution_times_ms.iter().cloned().fold(f64::INFINITY, f64::min);
+
+        results.push(JobResult {
+            matrix_1: job.matrix_1.clone(),
+            matrix_2: job.matrix_2.clone(),
+            algorithm: job.algorithm.clone(),
+            threshold,
+            n: matrix_1.shape()[0],
+            execution_times_ms,
+            mean_execution_time_ms,
+            min_execution_time_ms,
+            checksum: format!("{:016x}", compute_checksum(&result.unwrap())),
+        });
+    }
+
+    Ok(results)
+}
+
+/// FNV-1a hash of the matrix as it is printed with -p, so it can be checked against the printed output
+pub fn compute_checksum<T: Display>(matrix: &Array2<T>) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut hash_bytes = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+
+    for row in matrix.rows() {
+        for (j, value) in row.iter().enumerate() {
+            if j > 0 {
+                hash_bytes(b" ");
+            }
+            hash_bytes(value.to_string().as_bytes());
+        }
+        hash_bytes(b"\n");
+    }
+
+    hash
+}
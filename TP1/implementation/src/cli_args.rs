@@ -1,8 +1,12 @@
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use structopt::clap::{Error, ErrorKind};
 use structopt::StructOpt;
 
-#[derive(StructOpt)]
+use crate::matrix_utils::guess_format_from_extension;
+
+#[derive(StructOpt, Clone, Copy, PartialEq)]
 pub enum Algorithm {
     Conventional,
     Strassen,
@@ -21,12 +25,314 @@ impl FromStr for Algorithm {
     }
 }
 
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Algorithm::Conventional => "conv",
+            Algorithm::Strassen => "strassen",
+            Algorithm::StrassenThreshold => "strassenSeuil",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 3] = [Algorithm::Conventional, Algorithm::Strassen, Algorithm::StrassenThreshold];
+
+    /// Threshold below which Strassen multiplies conventionally, or None for the conventional algorithm
+    pub fn strassen_threshold(self, threshold: usize) -> Option<usize> {
+        match self {
+            Algorithm::Conventional => None,
+            Algorithm::Strassen => Some(0),
+            Algorithm::StrassenThreshold => Some(threshold),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ElementType {
+    Integer,
+    Float,
+    Modular,
+    BigInteger,
+    Gf2,
+    Complex,
+}
+
+impl FromStr for ElementType {
+    type Err = String;
+    fn from_str(element_type: &str) -> Result<Self, Self::Err> {
+        match element_type {
+            "i32" => Ok(ElementType::Integer),
+            "f64" => Ok(ElementType::Float),
+            "mod" => Ok(ElementType::Modular),
+            "bigint" => Ok(ElementType::BigInteger),
+            "gf2" => Ok(ElementType::Gf2),
+            "complex" => Ok(ElementType::Complex),
+            _ => Err(format!("Could not parse element type: {}", element_type)),
+        }
+    }
+}
+
+/// Element types stored directly in ndarray matrices, which the subcommands built on the generic algorithms support
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScalarType {
+    Integer,
+    Float,
+    Modular,
+}
+
+/// Element types of a field, which inversion and the determinant need to divide by their pivots
+#[derive(Clone, Copy, PartialEq)]
+pub enum FieldType {
+    Float,
+    Modular,
+}
+
+impl ElementType {
+    /// Returns the scalar type of the element type, or exits with the error message if it is not stored in ndarray matrices
+    fn require_scalar_type(self, message: &str) -> ScalarType {
+        match self {
+            ElementType::Integer => ScalarType::Integer,
+            ElementType::Float => ScalarType::Float,
+            ElementType::Modular => ScalarType::Modular,
+            ElementType::BigInteger | ElementType::Gf2 | ElementType::Complex => invalid_value(message),
+        }
+    }
+
+    /// Returns the field type of the element type, or exits with the error message if it has no division
+    fn require_field_type(self, message: &str) -> FieldType {
+        match self {
+            ElementType::Float => FieldType::Float,
+            ElementType::Modular => FieldType::Modular,
+            ElementType::Integer | ElementType::BigInteger | ElementType::Gf2 | ElementType::Complex => invalid_value(message),
+        }
+    }
+}
+
+/// Product of complex matrices: 4 real multiplications per complex product, or 3 real matrix products
+#[derive(Clone, Copy, PartialEq)]
+pub enum ComplexMethod {
+    FourMultiplications,
+    ThreeMultiplications,
+}
+
+impl FromStr for ComplexMethod {
+    type Err = String;
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method {
+            "4m" => Ok(ComplexMethod::FourMultiplications),
+            "3m" => Ok(ComplexMethod::ThreeMultiplications),
+            _ => Err(format!("Could not parse complex multiplication method: {}", method)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MatrixFormat {
+    Text,
+    Csv,
+    Npy,
+    MatrixMarket,
+    Binary,
+}
+
+impl FromStr for MatrixFormat {
+    type Err = String;
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "txt" => Ok(MatrixFormat::Text),
+            "csv" => Ok(MatrixFormat::Csv),
+            "npy" => Ok(MatrixFormat::Npy),
+            "mtx" => Ok(MatrixFormat::MatrixMarket),
+            "bin" => Ok(MatrixFormat::Binary),
+            _ => Err(format!("Could not parse matrix format: {}", format)),
+        }
+    }
+}
+
+/// Address of a worker: a TCP port on localhost, or the path of a Unix socket
+#[derive(Clone, PartialEq)]
+pub enum WorkerAddress {
+    Tcp(u16),
+    Unix(PathBuf),
+}
+
+impl FromStr for WorkerAddress {
+    type Err = String;
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        if address.is_empty() {
+            return Err("Worker address cannot be empty".to_string());
+        }
+        match address.parse() {
+            Ok(port) => Ok(WorkerAddress::Tcp(port)),
+            Err(_) => Ok(WorkerAddress::Unix(PathBuf::from(address))),
+        }
+    }
+}
+
+impl fmt::Display for WorkerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorkerAddress::Tcp(port) => write!(f, "127.0.0.1:{}", port),
+            WorkerAddress::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
 #[derive(StructOpt)]
 pub struct Cli {
     /// The multiplication algorithm to use
     #[structopt(short)]
-    pub algorithm: Algorithm,
+    pub algorithm: Option<Algorithm>,
+
+    /// Path to the first matrix file
+    #[structopt(long = "e1", parse(from_os_str))]
+    pub matrix_1_filename: Option<PathBuf>,
+
+    /// Path to the second matrix file
+    #[structopt(long = "e2", parse(from_os_str))]
+    pub matrix_2_filename: Option<PathBuf>,
+
+    /// Prints the resulting matrix
+    #[structopt(short = "p")]
+    pub show_result: bool,
+
+    /// Prints execution time in ns
+    #[structopt(short = "t")]
+    pub show_exec_time: bool,
+
+    /// Overrides the default matrix size threshold for the Strassen algorithm
+    #[structopt(long = "threshold", default_value = "16")]
+    pub threshold: usize,
+
+    /// Type of the matrix elements (i32, f64, mod, bigint, gf2 or complex)
+    #[structopt(long = "type", default_value = "i32")]
+    pub element_type: ElementType,
+
+    /// Prime modulus used when the element type is mod
+    #[structopt(long = "modulus", default_value = "1000000007")]
+    pub modulus: u64,
+
+    /// Product of complex matrices: 4m for 4 real multiplications per element product, or 3m for 3 real matrix products
+    #[structopt(long = "complex-method", default_value = "3m")]
+    pub complex_method: ComplexMethod,
+
+    /// Writes the resulting matrix to a file ("-" for stdout)
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    pub output_filename: Option<PathBuf>,
+
+    /// Format of the output file (txt, csv, npy, mtx or bin), guessed from its extension by default
+    #[structopt(long = "output-format")]
+    pub output_format: Option<MatrixFormat>,
+
+    /// Writes a trace of the Strassen recursive calls, readable by Chrome trace-event viewers
+    #[structopt(long = "trace", parse(from_os_str))]
+    pub trace_filename: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Returns the algorithm and matrix files, which are only required when no subcommand is given
+    pub fn multiplication_args(&self) -> (Algorithm, &Path, &Path) {
+        (
+            require_arg(self.algorithm, "-a <algorithm>"),
+            require_arg(self.matrix_1_filename.as_deref(), "--e1 <matrix-1-filename>"),
+            require_arg(self.matrix_2_filename.as_deref(), "--e2 <matrix-2-filename>"),
+        )
+    }
+
+    /// Returns the Strassen threshold of the traced multiplication if a trace is requested
+    pub fn trace_threshold(&self) -> Option<usize> {
+        self.trace_filename.as_ref()?;
+        let (algorithm, _, _) = self.multiplication_args();
+        Some(
+            algorithm
+                .strassen_threshold(self.threshold)
+                .unwrap_or_else(|| invalid_value("Traces are only available for the strassen and strassenSeuil algorithms")),
+        )
+    }
+
+    /// Exits with an error if a trace is requested for an element type whose multiplication is not traced
+    pub fn require_no_trace(&self, element_type_name: &str) {
+        if self.trace_filename.is_some() {
+            invalid_value(&format!("Traces are not available for {} elements", element_type_name));
+        }
+    }
+
+    /// Returns the output file of an element type that can only be written in the txt format, or exits with an error if
+    /// another format is requested
+    pub fn text_output_filename(&self, element_type_name: &str) -> Option<&Path> {
+        let output_filename = self.output_filename.as_deref()?;
+        let format = self.output_format.unwrap_or_else(|| guess_format_from_extension(output_filename));
+        if format != MatrixFormat::Text {
+            invalid_value(&format!("{} matrices can only be written in the txt format", element_type_name));
+        }
+        Some(output_filename)
+    }
+
+    /// Exits with an error if an output file is requested for an element type whose result can only be printed
+    pub fn require_no_output_file(&self, element_type_name: &str) {
+        if self.output_filename.is_some() {
+            invalid_value(&format!(
+                "Output files are not supported for {} elements, print the result with -p instead",
+                element_type_name
+            ));
+        }
+    }
+}
+
+fn require_arg<T>(value: Option<T>, name: &str) -> T {
+    value.unwrap_or_else(|| {
+        let message = format!("The following required argument was not provided: {}", name);
+        Error::with_description(&message, ErrorKind::MissingRequiredArgument).exit()
+    })
+}
+
+fn invalid_value(message: &str) -> ! {
+    Error::with_description(message, ErrorKind::InvalidValue).exit()
+}
+
+fn parse_positive_count(count: &str) -> Result<usize, String> {
+    match count.parse() {
+        Ok(0) => Err("Count must be at least 1".to_string()),
+        Ok(count) => Ok(count),
+        Err(_) => Err(format!("Could not parse count: {}", count)),
+    }
+}
+
+#[derive(StructOpt)]
+pub enum Command {
+    /// Reports the floating-point error of each algorithm against a compensated-summation reference
+    ErrorReport(ErrorReportArgs),
+
+    /// Runs every multiplication job of a CSV or JSON manifest in a single process
+    Batch(BatchArgs),
+
+    /// Inverts a matrix with recursive block inversion built on Strassen
+    Invert(InversionArgs),
 
+    /// Computes the determinant of a matrix with an LU decomposition
+    Determinant(InversionArgs),
+
+    /// Computes C = alpha * op(A) * op(B) + beta * C, where op optionally transposes its matrix
+    Gemm(GemmArgs),
+
+    /// Checks every algorithm against the conventional algorithm, and block inversion against Gauss-Jordan, on seeded
+    /// random matrices
+    Selfcheck(SelfcheckArgs),
+
+    /// Serves Strassen sub-products for a distribute coordinator
+    Worker(WorkerArgs),
+
+    /// Multiplies two matrices by sending Strassen sub-products to worker processes
+    Distribute(DistributeArgs),
+}
+
+#[derive(StructOpt)]
+pub struct ErrorReportArgs {
     /// Path to the first matrix file
     #[structopt(long = "e1", parse(from_os_str))]
     pub matrix_1_filename: PathBuf,
@@ -35,15 +341,236 @@ pub struct Cli {
     #[structopt(long = "e2", parse(from_os_str))]
     pub matrix_2_filename: PathBuf,
 
+    /// Smallest Strassen threshold to evaluate (doubled until the matrix size is reached)
+    #[structopt(long = "min-threshold", default_value = "1")]
+    pub min_threshold: usize,
+}
+
+#[derive(StructOpt)]
+pub struct BatchArgs {
+    /// Path to the manifest listing the matrix pairs and algorithms (.json or .csv)
+    #[structopt(parse(from_os_str))]
+    pub manifest_filename: PathBuf,
+
+    /// Path to the JSON file where the results are written
+    #[structopt(short = "o", long = "output", parse(from_os_str), default_value = "batch_results.json")]
+    pub output_filename: PathBuf,
+
+    /// Number of times each job is executed
+    #[structopt(short = "r", long = "repeat", default_value = "1", parse(try_from_str = parse_positive_count))]
+    pub repeat_count: usize,
+
+    /// Type of the matrix elements (i32, f64 or mod)
+    #[structopt(long = "type", default_value = "i32")]
+    pub element_type: ElementType,
+
+    /// Prime modulus used when the element type is mod
+    #[structopt(long = "modulus", default_value = "1000000007")]
+    pub modulus: u64,
+}
+
+impl BatchArgs {
+    pub fn scalar_type(&self) -> ScalarType {
+        self.element_type.require_scalar_type("Batch mode requires i32, f64 or mod elements")
+    }
+}
+
+#[derive(StructOpt)]
+pub struct InversionArgs {
+    /// Path to the matrix file
+    #[structopt(parse(from_os_str))]
+    pub matrix_filename: PathBuf,
+
+    /// Type of the matrix elements (f64 or mod)
+    #[structopt(long = "type", default_value = "f64")]
+    pub element_type: ElementType,
+
+    /// Prime modulus used when the element type is mod
+    #[structopt(long = "modulus", default_value = "1000000007")]
+    pub modulus: u64,
+
+    /// Matrix size threshold for the Strassen algorithm and the block recursion
+    #[structopt(long = "threshold", default_value = "16")]
+    pub threshold: usize,
+
+    /// Prints the resulting inverse or determinant
+    #[structopt(short = "p")]
+    pub show_result: bool,
+
+    /// Writes the inverse to a file ("-" for stdout)
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    pub output_filename: Option<PathBuf>,
+
+    /// Format of the output file (txt, csv, npy, mtx or bin), guessed from its extension by default
+    #[structopt(long = "output-format")]
+    pub output_format: Option<MatrixFormat>,
+
+    /// Prints execution time in ms
+    #[structopt(short = "t")]
+    pub show_exec_time: bool,
+}
+
+impl InversionArgs {
+    pub fn field_type(&self) -> FieldType {
+        self.element_type.require_field_type("Inversion and determinant require f64 or mod elements")
+    }
+}
+
+#[derive(StructOpt)]
+pub struct GemmArgs {
+    /// The multiplication algorithm to use
+    #[structopt(short)]
+    pub algorithm: Algorithm,
+
+    /// Path to the matrix A file
+    #[structopt(long = "e1", parse(from_os_str))]
+    pub matrix_1_filename: PathBuf,
+
+    /// Path to the matrix B file
+    #[structopt(long = "e2", parse(from_os_str))]
+    pub matrix_2_filename: PathBuf,
+
+    /// Path to the matrix C file to accumulate into
+    #[structopt(long = "c", parse(from_os_str))]
+    pub matrix_3_filename: Option<PathBuf>,
+
+    /// Uses the transpose of A
+    #[structopt(long = "transpose-a")]
+    pub transpose_1: bool,
+
+    /// Uses the transpose of B
+    #[structopt(long = "transpose-b")]
+    pub transpose_2: bool,
+
+    /// Scalar multiplying the product
+    #[structopt(long = "alpha", default_value = "1", allow_hyphen_values = true)]
+    pub alpha: String,
+
+    /// Scalar multiplying C, which defaults to 1 if C is given and 0 otherwise
+    #[structopt(long = "beta", allow_hyphen_values = true)]
+    pub beta: Option<String>,
+
+    /// Overrides the default matrix size threshold for the Strassen algorithm
+    #[structopt(long = "threshold", default_value = "16")]
+    pub threshold: usize,
+
+    /// Type of the matrix elements (i32, f64 or mod)
+    #[structopt(long = "type", default_value = "i32")]
+    pub element_type: ElementType,
+
+    /// Prime modulus used when the element type is mod
+    #[structopt(long = "modulus", default_value = "1000000007")]
+    pub modulus: u64,
+
     /// Prints the resulting matrix
     #[structopt(short = "p")]
     pub show_result: bool,
 
-    /// Prints execution time in ns
+    /// Prints execution time in ms
     #[structopt(short = "t")]
     pub show_exec_time: bool,
 
+    /// Writes the resulting matrix to a file ("-" for stdout)
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    pub output_filename: Option<PathBuf>,
+
+    /// Format of the output file (txt, csv, npy, mtx or bin), guessed from its extension by default
+    #[structopt(long = "output-format")]
+    pub output_format: Option<MatrixFormat>,
+}
+
+impl GemmArgs {
+    pub fn scalar_type(&self) -> ScalarType {
+        self.element_type.require_scalar_type("GEMM requires i32, f64 or mod elements")
+    }
+}
+
+#[derive(StructOpt)]
+pub struct SelfcheckArgs {
+    /// Seed of the random matrix generator
+    #[structopt(long = "seed", default_value = "8775")]
+    pub seed: u64,
+
+    /// Largest matrix size to check (rounded down to a power of two for products)
+    #[structopt(long = "max-size", default_value = "64")]
+    pub max_size: usize,
+
+    /// Number of random matrix pairs per size and value range
+    #[structopt(long = "cases", default_value = "3")]
+    pub case_count: usize,
+
+    /// Directory where the minimal failing cases are saved
+    #[structopt(long = "failures-dir", parse(from_os_str), default_value = "selfcheck_failures")]
+    pub failures_directory: PathBuf,
+}
+
+#[derive(StructOpt)]
+pub struct WorkerArgs {
+    /// TCP port on localhost, or path of the Unix socket, to listen on
+    #[structopt(long = "listen")]
+    pub address: WorkerAddress,
+}
+
+#[derive(StructOpt)]
+pub struct DistributeArgs {
+    /// The Strassen algorithm to distribute (strassen or strassenSeuil)
+    #[structopt(short)]
+    pub algorithm: Algorithm,
+
+    /// Path to the first matrix file
+    #[structopt(long = "e1", parse(from_os_str))]
+    pub matrix_1_filename: PathBuf,
+
+    /// Path to the second matrix file
+    #[structopt(long = "e2", parse(from_os_str))]
+    pub matrix_2_filename: PathBuf,
+
+    /// Comma-separated worker addresses, as TCP ports on localhost or Unix socket paths
+    #[structopt(long = "workers", use_delimiter = true, required = true)]
+    pub worker_addresses: Vec<WorkerAddress>,
+
+    /// Recursion depth of the sub-products sent to the workers (7^depth sub-products)
+    #[structopt(long = "depth", default_value = "1")]
+    pub depth: usize,
+
     /// Overrides the default matrix size threshold for the Strassen algorithm
     #[structopt(long = "threshold", default_value = "16")]
     pub threshold: usize,
+
+    /// Type of the matrix elements (i32, f64 or mod)
+    #[structopt(long = "type", default_value = "i32")]
+    pub element_type: ElementType,
+
+    /// Prime modulus used when the element type is mod
+    #[structopt(long = "modulus", default_value = "1000000007")]
+    pub modulus: u64,
+
+    /// Prints the resulting matrix
+    #[structopt(short = "p")]
+    pub show_result: bool,
+
+    /// Prints execution time in ms
+    #[structopt(short = "t")]
+    pub show_exec_time: bool,
+
+    /// Writes the resulting matrix to a file ("-" for stdout)
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    pub output_filename: Option<PathBuf>,
+
+    /// Format of the output file (txt, csv, npy, mtx or bin), guessed from its extension by default
+    #[structopt(long = "output-format")]
+    pub output_format: Option<MatrixFormat>,
+}
+
+impl DistributeArgs {
+    /// Returns the Strassen threshold of the distributed multiplication, which the conventional algorithm does not have
+    pub fn strassen_threshold(&self) -> usize {
+        self.algorithm
+            .strassen_threshold(self.threshold)
+            .unwrap_or_else(|| invalid_value("Only the strassen and strassenSeuil algorithms can be distributed"))
+    }
+
+    pub fn scalar_type(&self) -> ScalarType {
+        self.element_type.require_scalar_type("Distributed mode requires i32, f64 or mod elements")
+    }
 }
@@ -0,0 +1,143 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use ndarray::{Array2, Zip};
+use num_complex::Complex64;
+
+use crate::cli_args::{Algorithm, Cli, ComplexMethod};
+use crate::multiplication_algorithms::multiply_matrices;
+
+pub fn run_complex_multiplication(args: &Cli) {
+    let (algorithm, matrix_1_filename, matrix_2_filename) = args.multiplication_args();
+    args.require_no_trace("complex");
+    let output_filename = args.text_output_filename("complex");
+
+    // Load matrices
+    let matrix_1 = load_complex_matrix(matrix_1_filename).expect("Error parsing matrix 1 from file");
+    let matrix_2 = load_complex_matrix(matrix_2_filename).expect("Error parsing matrix 2 from file");
+
+    assert_eq!(matrix_1.shape(), matrix_2.shape(), "Incompatible shapes between matrices");
+
+    // Start clock
+    let now = Instant::now();
+
+    // Execute selected algorithm
+    let result = match args.complex_method {
+        ComplexMethod::FourMultiplications => multiply_matrices(algorithm, &matrix_1, &matrix_2, args.threshold),
+        ComplexMethod::ThreeMultiplications => multiply_matrices_3m(algorithm, &matrix_1, &matrix_2, args.threshold),
+    };
+
+    // Calculate elapsed time
+    let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
+
+    if args.show_result {
+        let stdout = io::stdout();
+        let mut writer = BufWriter::new(stdout.lock());
+        write_complex_rows(&result, &mut writer)
+            .and_then(|_| writer.flush())
+            .expect("Error printing matrix");
+    }
+
+    if let Some(output_filename) = output_filename {
+        save_complex_matrix(&result, output_filename).expect("Error writing resulting matrix");
+    }
+
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
+    }
+}
+
+/// 3M method: with A = Ar + i Ai and B = Br + i Bi, the real part of AB is Ar Br - Ai Bi and its imaginary part is
+/// (Ar + Ai)(Br + Bi) - Ar Br - Ai Bi, so three real matrix products are enough instead of four
+pub fn multiply_matrices_3m(algorithm: Algorithm, matrix_1: &Array2<Complex64>, matrix_2: &Array2<Complex64>, threshold: usize) -> Array2<Complex64> {
+    let (real_1, imaginary_1) = (matrix_1.mapv(|z| z.re), matrix_1.mapv(|z| z.im));
+    let (real_2, imaginary_2) = (matrix_2.mapv(|z| z.re), matrix_2.mapv(|z| z.im));
+
+    let real_product = multiply_matrices(algorithm, &real_1, &real_2, threshold);
+    let imaginary_product = multiply_matrices(algorithm, &imaginary_1, &imaginary_2, threshold);
+    let sum_product = multiply_matrices(algorithm, &(&real_1 + &imaginary_1), &(&real_2 + &imaginary_2), threshold);
+
+    Zip::from(&real_product)
+        .and(&imaginary_product)
+        .and(&sum_product)
+        .map_collect(|&real, &imaginary, &sum| Complex64::new(real - imaginary, sum - real - imaginary))
+}
+
+/// Same format as the input files, where each row holds interleaved real and imaginary parts
+fn load_complex_matrix(filename: &Path) -> Result<Array2<Complex64>, Box<dyn Error>> {
+    let mut lines_it = BufReader::new(File::open(filename)?).lines();
+
+    // Read matrix size
+    let first_line = lines_it.next().ok_or("Empty matrix file")??;
+    let matrix_size = usize::pow(2, first_line.trim().parse()?);
+
+    // Read matrix
+    let mut matrix = Array2::zeros((matrix_size, matrix_size));
+    for (i, line) in lines_it.enumerate() {
+        let parts = line?.split_whitespace().map(str::parse).collect::<Result<Vec<f64>, _>>()?;
+        if parts.len() != 2 * matrix_size {
+            return Err(format!("Row {} has {} values instead of {} interleaved real and imaginary parts", i, parts.len(), 2 * matrix_size).into());
+        }
+        for (j, pair) in parts.chunks(2).enumerate() {
+            matrix[[i, j]] = Complex64::new(pair[0], pair[1]);
+        }
+    }
+
+    Ok(matrix)
+}
+
+fn save_complex_matrix(matrix: &Array2<Complex64>, filename: &Path) -> io::Result<()> {
+    let mut writer: Box<dyn Write> = if filename == Path::new("-") {
+        Box::new(BufWriter::new(io::stdout()))
+    } else {
+        Box::new(BufWriter::new(File::create(filename)?))
+    };
+
+    writeln!(writer, "{}", matrix.nrows().trailing_zeros())?;
+    write_complex_rows(matrix, &mut writer)?;
+    writer.flush()
+}
+
+fn write_complex_rows<W: Write>(matrix: &Array2<Complex64>, writer: &mut W) -> io::Result<()> {
+    for row in matrix.rows() {
+        for (j, value) in row.iter().enumerate() {
+            if j > 0 {
+                writer.write_all(b" ")?;
+            }
+            write!(writer, "{} {}", value.re, value.im)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// Small integer parts, so that every product and sum is exact in f64 and both methods must agree exactly
+    fn random_integer_complex_matrix(rng: &mut StdRng, n: usize) -> Array2<Complex64> {
+        Array2::from_shape_simple_fn((n, n), || {
+            Complex64::new(rng.gen_range(-100..=100) as f64, rng.gen_range(-100..=100) as f64)
+        })
+    }
+
+    #[test]
+    fn three_multiplications_match_four_multiplications() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for &n in &[1, 2, 16, 32] {
+            let matrix_1 = random_integer_complex_matrix(&mut rng, n);
+            let matrix_2 = random_integer_complex_matrix(&mut rng, n);
+            for &algorithm in &Algorithm::ALL {
+                let expected = multiply_matrices(algorithm, &matrix_1, &matrix_2, 4);
+                assert_eq!(multiply_matrices_3m(algorithm, &matrix_1, &matrix_2, 4), expected);
+            }
+        }
+    }
+}
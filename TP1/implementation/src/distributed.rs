@@ -0,0 +1,342 @@
+use std::convert::TryInto;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::process;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use ndarray::{Array2, ArrayView2};
+
+use crate::cli_args::{DistributeArgs, ScalarType, WorkerAddress, WorkerArgs};
+use crate::matrix_element::{BinaryElement, MatrixElement};
+use crate::matrix_utils::{load_matrix, load_matrix_binary, print_matrix, save_matrix, write_matrix_binary};
+use crate::modular_integer::{set_modulus, ModularInteger};
+use crate::multiplication_algorithms::{
+    concatenate_quadrants, multiply_matrices, multiply_matrices_strassen_threshold, strassen_operands, strassen_result_quadrants,
+};
+
+/// Status byte of a worker response
+const STATUS_OK: u8 = 0;
+const STATUS_ERROR: u8 = 1;
+
+/// Stream to a worker, over TCP or a Unix socket
+trait Connection: Read + Write + Sized + Send {
+    fn try_clone(&self) -> io::Result<Self>;
+}
+
+impl Connection for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+}
+
+impl Connection for UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        UnixStream::try_clone(self)
+    }
+}
+
+/// Serves requests until the process is killed. A request is the element type code, the modulus and the threshold,
+/// followed by both matrices in the binary format. The response is a status byte followed by the product in the
+/// binary format, or by the length and text of an error message.
+pub fn run_worker(args: &WorkerArgs) {
+    match &args.address {
+        WorkerAddress::Tcp(port) => {
+            let listener = TcpListener::bind(("127.0.0.1", *port)).expect("Error listening on TCP port");
+            eprintln!("Worker listening on {}", args.address);
+            for stream in listener.incoming() {
+                serve_connection(stream.expect("Error accepting connection"));
+            }
+        }
+        WorkerAddress::Unix(path) => {
+            // Remove the socket left by a previous worker, but never a regular file
+            if fs::metadata(path).map(|metadata| metadata.file_type().is_socket()).unwrap_or(false) {
+                fs::remove_file(path).expect("Error removing previous Unix socket");
+            }
+            let listener = UnixListener::bind(path).expect("Error listening on Unix socket");
+            eprintln!("Worker listening on {}", args.address);
+            for stream in listener.incoming() {
+                serve_connection(stream.expect("Error accepting connection"));
+            }
+        }
+    }
+}
+
+/// Handles the requests of a connection one at a time, since the modulus of ModularInteger is global
+fn serve_connection<C: Connection>(stream: C) {
+    let mut reader = BufReader::new(stream.try_clone().expect("Error cloning connection"));
+    let mut writer = BufWriter::new(stream);
+
+    loop {
+        let mut request_header = [0u8; 17];
+        match reader.read_exact(&mut request_header) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return,
+            Err(error) => {
+                eprintln!("Error reading request: {}", error);
+                return;
+            }
+        }
+
+        let type_code = request_header[0];
+        let modulus = u64::from_le_bytes(request_header[1..9].try_into().unwrap());
+        let threshold = u64::from_le_bytes(request_header[9..17].try_into().unwrap()) as usize;
+
+        let response = match type_code {
+            i32::BINARY_TYPE_CODE => serve_request::<i32, _, _>(&mut reader, &mut writer, threshold),
+            f64::BINARY_TYPE_CODE => serve_request::<f64, _, _>(&mut reader, &mut writer, threshold),
+            ModularInteger::BINARY_TYPE_CODE => set_modulus(modulus)
+                .map_err(|error| error.to_string().into())
+                .and_then(|_| serve_request::<ModularInteger, _, _>(&mut reader, &mut writer, threshold)),
+            _ => Err(format!("Unsupported element type code: {}", type_code).into()),
+        };
+
+        // Report the error to the coordinator, then drop the connection since the rest of the request is unread
+        if let Err(error) = response {
+            eprintln!("Error serving request: {}", error);
+            let message = error.to_string();
+            let _ = writer
+                .write_all(&[STATUS_ERROR])
+                .and_then(|_| writer.write_all(&(message.len() as u64).to_le_bytes()))
+                .and_then(|_| writer.write_all(message.as_bytes()))
+                .and_then(|_| writer.flush());
+            return;
+        }
+    }
+}
+
+fn serve_request<T, R, W>(reader: &mut R, writer: &mut W, threshold: usize) -> Result<(), Box<dyn Error>>
+where
+    T: MatrixElement + BinaryElement,
+    R: Read,
+    W: Write,
+{
+    let matrix_1: Array2<T> = load_matrix_binary(reader)?;
+    let matrix_2: Array2<T> = load_matrix_binary(reader)?;
+
+    if !matrix_1.is_square() || matrix_1.dim() != matrix_2.dim() || !matrix_1.nrows().is_power_of_two() {
+        return Err("Sub-products require square matrices of the same power of two size".into());
+    }
+
+    let result = multiply_matrices_strassen_threshold(&matrix_1, &matrix_2, threshold);
+
+    writer.write_all(&[STATUS_OK])?;
+    write_matrix_binary(&result, writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn run_distributed(args: &DistributeArgs) {
+    match args.scalar_type() {
+        ScalarType::Integer => run_distributed_with_type::<i32>(args),
+        ScalarType::Float => run_distributed_with_type::<f64>(args),
+        ScalarType::Modular => {
+            set_modulus(args.modulus).expect("Invalid modulus");
+            run_distributed_with_type::<ModularInteger>(args)
+        }
+    }
+}
+
+fn run_distributed_with_type<T>(args: &DistributeArgs)
+where
+    T: MatrixElement + BinaryElement + FromStr + PartialEq,
+    T::Err: Error + 'static,
+{
+    let threshold = args.strassen_threshold();
+
+    // Load matrices
+    let matrix_1: Array2<T> = load_matrix(&args.matrix_1_filename).expect("Error parsing matrix 1 from file");
+    let matrix_2: Array2<T> = load_matrix(&args.matrix_2_filename).expect("Error parsing matrix 2 from file");
+
+    assert_eq!(matrix_1.shape(), matrix_2.shape(), "Incompatible shapes between matrices");
+    assert!(
+        matrix_1.is_square() && matrix_1.nrows().is_power_of_two(),
+        "Matrices must be square with a power of two size"
+    );
+
+    // Start clock
+    let now = Instant::now();
+
+    let result = multiply_matrices_distributed(&matrix_1, &matrix_2, threshold, args.depth, &args.worker_addresses, args.modulus)
+        .expect("Error running distributed multiplication");
+
+    // Calculate elapsed time
+    let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
+
+    // The top levels of the recursion are the same in both cases, so even floating-point results must be identical
+    let single_process_result = multiply_matrices(args.algorithm, &matrix_1, &matrix_2, args.threshold);
+    if result != single_process_result {
+        eprintln!("Distributed result differs from the single-process result");
+        process::exit(1);
+    }
+    eprintln!("Distributed result matches the single-process result");
+
+    if args.show_result {
+        print_matrix(&result)
+    }
+
+    if let Some(output_filename) = &args.output_filename {
+        save_matrix(&result, output_filename, args.output_format).expect("Error writing resulting matrix");
+    }
+
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
+    }
+}
+
+/// Splits the product into the Strassen sub-products at the given depth, computes them on the workers and assembles C.
+/// Each worker takes the next remaining sub-product as soon as it is done with the previous one.
+pub fn multiply_matrices_distributed<T>(
+    matrix_1: &Array2<T>,
+    matrix_2: &Array2<T>,
+    threshold: usize,
+    depth: usize,
+    worker_addresses: &[WorkerAddress],
+    modulus: u64,
+) -> Result<Array2<T>, Box<dyn Error>>
+where
+    T: MatrixElement + BinaryElement,
+{
+    let mut sub_problems = Vec::new();
+    split_sub_problems(matrix_1.view(), matrix_2.view(), threshold, depth, &mut sub_problems);
+
+    let next_sub_problem = Mutex::new(0usize);
+    let products: Mutex<Vec<Option<Array2<T>>>> = Mutex::new(vec![None; sub_problems.len()]);
+
+    let worker_results: Vec<Result<(), String>> = thread::scope(|scope| {
+        let (sub_problems, next_sub_problem, products) = (&sub_problems, &next_sub_problem, &products);
+        let handles: Vec<_> = worker_addresses
+            .iter()
+            .map(|address| {
+                scope.spawn(move || {
+                    let result = match address {
+                        WorkerAddress::Tcp(port) => TcpStream::connect(("127.0.0.1", *port))
+                            .map_err(Box::from)
+                            .and_then(|stream| compute_sub_products(stream, sub_problems, next_sub_problem, products, threshold, modulus)),
+                        WorkerAddress::Unix(path) => UnixStream::connect(path)
+                            .map_err(Box::from)
+                            .and_then(|stream| compute_sub_products(stream, sub_problems, next_sub_problem, products, threshold, modulus)),
+                    };
+                    result.map_err(|error| format!("Worker {}: {}", address, error))
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+
+    // A sub-product is missing if its worker failed
+    let products = products.into_inner().unwrap();
+    if products.iter().any(Option::is_none) {
+        let errors: Vec<String> = worker_results.into_iter().filter_map(Result::err).collect();
+        return Err(format!("Some sub-products were not computed: {}", errors.join(", ")).into());
+    }
+
+    let mut products_it = products.into_iter().map(Option::unwrap);
+    Ok(assemble_sub_products(&mut products_it, matrix_1.nrows(), threshold, depth))
+}
+
+fn compute_sub_products<T, C>(
+    stream: C,
+    sub_problems: &[(Array2<T>, Array2<T>)],
+    next_sub_problem: &Mutex<usize>,
+    products: &Mutex<Vec<Option<Array2<T>>>>,
+    threshold: usize,
+    modulus: u64,
+) -> Result<(), Box<dyn Error>>
+where
+    T: MatrixElement + BinaryElement,
+    C: Connection,
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+
+    loop {
+        let index = {
+            let mut next_sub_problem = next_sub_problem.lock().unwrap();
+            if *next_sub_problem == sub_problems.len() {
+                return Ok(());
+            }
+            *next_sub_problem += 1;
+            *next_sub_problem - 1
+        };
+
+        let product = request_product(&mut reader, &mut writer, &sub_problems[index], threshold, modulus)?;
+        products.lock().unwrap()[index] = Some(product);
+    }
+}
+
+fn request_product<T, R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    (matrix_1, matrix_2): &(Array2<T>, Array2<T>),
+    threshold: usize,
+    modulus: u64,
+) -> Result<Array2<T>, Box<dyn Error>>
+where
+    T: BinaryElement,
+    R: Read,
+    W: Write,
+{
+    writer.write_all(&[T::BINARY_TYPE_CODE])?;
+    writer.write_all(&modulus.to_le_bytes())?;
+    writer.write_all(&(threshold as u64).to_le_bytes())?;
+    write_matrix_binary(matrix_1, writer)?;
+    write_matrix_binary(matrix_2, writer)?;
+    writer.flush()?;
+
+    let mut status = [0u8; 1];
+    reader.read_exact(&mut status)?;
+    if status[0] == STATUS_OK {
+        return load_matrix_binary(reader);
+    }
+
+    let mut length = [0u8; 8];
+    reader.read_exact(&mut length)?;
+    let mut message = vec![0u8; u64::from_le_bytes(length) as usize];
+    reader.read_exact(&mut message)?;
+    Err(String::from_utf8_lossy(&message).into())
+}
+
+/// A product is split while the single-process Strassen would split it, so that both compute the same operations
+fn is_split(n: usize, threshold: usize, depth: usize) -> bool {
+    depth > 0 && n > 1 && n > threshold
+}
+
+/// Operands of the sub-products at the given depth, in the order of the Strassen recursion
+fn split_sub_problems<T: MatrixElement>(
+    matrix_1: ArrayView2<T>,
+    matrix_2: ArrayView2<T>,
+    threshold: usize,
+    depth: usize,
+    sub_problems: &mut Vec<(Array2<T>, Array2<T>)>,
+) {
+    if !is_split(matrix_1.nrows(), threshold, depth) {
+        sub_problems.push((matrix_1.to_owned(), matrix_2.to_owned()));
+        return;
+    }
+
+    for (operand_1, operand_2) in strassen_operands(&matrix_1, &matrix_2).iter() {
+        split_sub_problems(operand_1.view(), operand_2.view(), threshold, depth - 1, sub_problems);
+    }
+}
+
+/// Combines the sub-products, in the order of split_sub_problems, into the n x n product
+fn assemble_sub_products<T, I>(products: &mut I, n: usize, threshold: usize, depth: usize) -> Array2<T>
+where
+    T: MatrixElement,
+    I: Iterator<Item = Array2<T>>,
+{
+    if !is_split(n, threshold, depth) {
+        return products.next().unwrap();
+    }
+
+    let intermediate_matrices: Vec<Array2<T>> = (0..7).map(|_| assemble_sub_products(products, n / 2, threshold, depth - 1)).collect();
+    concatenate_quadrants(&strassen_result_quadrants(&intermediate_matrices))
+}
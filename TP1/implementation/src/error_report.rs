@@ -0,0 +1,189 @@
+use std::process;
+
+use ndarray::Array2;
+
+use crate::cli_args::ErrorReportArgs;
+use crate::matrix_utils::load_matrix;
+use crate::multiplication_algorithms::{multiply_matrices_conventional, multiply_matrices_strassen_threshold};
+
+pub fn run_error_report(args: &ErrorReportArgs) {
+    // Load matrices
+    let matrix_1: Array2<f64> = load_matrix(&args.matrix_1_filename).expect("Error parsing matrix 1 from file");
+    let matrix_2: Array2<f64> = load_matrix(&args.matrix_2_filename).expect("Error parsing matrix 2 from file");
+
+    let rows = compute_error_report(&matrix_1, &matrix_2, args.min_threshold).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+
+    println!("{:<16}{:>10}{:>8}{:>18}{:>18}{:>10}", "algorithm", "threshold", "depth", "max_abs_error", "rel_frob_error", "growth");
+    for row in rows {
+        let threshold = row.threshold.map_or_else(|| String::from("-"), |threshold| threshold.to_string());
+        let growth = row.growth.map_or_else(|| String::from("-"), |growth| format!("{:.3}", growth));
+        println!(
+            "{:<16}{:>10}{:>8}{:>18.6e}{:>18.6e}{:>10}",
+            row.algorithm_name, threshold, row.depth, row.max_abs_error, row.relative_frobenius_error, growth
+        );
+    }
+}
+
+/// Errors of one algorithm against the compensated reference
+struct ErrorReportRow {
+    algorithm_name: &'static str,
+    /// Strassen threshold, or None for the conventional algorithm
+    threshold: Option<usize>,
+    depth: u32,
+    max_abs_error: f64,
+    relative_frobenius_error: f64,
+    /// Error ratio relative to the run with one less recursion level, if that run has a nonzero error
+    growth: Option<f64>,
+}
+
+/// Computes the errors of the conventional algorithm, then of Strassen from a single recursion level down to full
+/// recursion, on square matrices of the same power of two size
+fn compute_error_report(matrix_1: &Array2<f64>, matrix_2: &Array2<f64>, min_threshold: usize) -> Result<Vec<ErrorReportRow>, String> {
+    if matrix_1.shape() != matrix_2.shape() {
+        return Err("Incompatible shapes between matrices".to_string());
+    }
+    if !matrix_1.is_square() || !matrix_1.nrows().is_power_of_two() {
+        return Err(format!("Matrices must be square with a power of two size, got {}x{}", matrix_1.nrows(), matrix_1.ncols()));
+    }
+
+    let n = matrix_1.shape()[0];
+    let reference = multiply_matrices_compensated(matrix_1, matrix_2);
+    let mut rows = Vec::new();
+
+    // Conventional algorithm, which only suffers from the rounding of the naive dot products
+    let conventional_result = multiply_matrices_conventional(matrix_1, matrix_2);
+    let (max_abs_error, relative_frobenius_error) = compute_errors(&conventional_result, &reference);
+    rows.push(ErrorReportRow {
+        algorithm_name: "conv",
+        threshold: None,
+        depth: 0,
+        max_abs_error,
+        relative_frobenius_error,
+        growth: None,
+    });
+
+    // Strassen with every power of two threshold, from a single recursion level down to full recursion
+    let mut thresholds = Vec::new();
+    let mut threshold = min_threshold.max(1).next_power_of_two();
+    while threshold < n {
+        thresholds.push(threshold);
+        threshold *= 2;
+    }
+
+    let mut previous_relative_frobenius_error = relative_frobenius_error;
+    for &threshold in thresholds.iter().rev() {
+        let result = multiply_matrices_strassen_threshold(matrix_1, matrix_2, threshold);
+        let (max_abs_error, relative_frobenius_error) = compute_errors(&result, &reference);
+
+        rows.push(ErrorReportRow {
+            algorithm_name: if threshold == 1 { "strassen" } else { "strassenSeuil" },
+            threshold: Some(threshold),
+            depth: recursion_depth(n, threshold),
+            max_abs_error,
+            relative_frobenius_error,
+            growth: (previous_relative_frobenius_error > 0.0).then(|| relative_frobenius_error / previous_relative_frobenius_error),
+        });
+
+        previous_relative_frobenius_error = relative_frobenius_error;
+    }
+
+    Ok(rows)
+}
+
+/// Number of Strassen recursion levels performed on an n x n matrix before reaching the threshold
+fn recursion_depth(n: usize, threshold: usize) -> u32 {
+    let mut depth = 0;
+    let mut size = n;
+    while size > threshold.max(1) {
+        size /= 2;
+        depth += 1;
+    }
+    depth
+}
+
+/// Conventional product computed with compensated dot products (error-free products and Neumaier summation)
+fn multiply_matrices_compensated(matrix_1: &Array2<f64>, matrix_2: &Array2<f64>) -> Array2<f64> {
+    let n = matrix_1.shape()[0];
+    let mut result = Array2::zeros((n, n));
+
+    for i in 0..n {
+        for j in 0..n {
+            let mut sum = 0.0;
+            let mut compensation = 0.0;
+
+            for k in 0..n {
+                let product = matrix_1[[i, k]] * matrix_2[[k, j]];
+                let product_error = matrix_1[[i, k]].mul_add(matrix_2[[k, j]], -product);
+
+                let new_sum = sum + product;
+                let sum_error = if f64::abs(sum) >= f64::abs(product) {
+                    (sum - new_sum) + product
+                } else {
+                    (product - new_sum) + sum
+                };
+
+                sum = new_sum;
+                compensation += sum_error + product_error;
+            }
+
+            result[[i, j]] = sum + compensation;
+        }
+    }
+
+    result
+}
+
+/// Returns the maximum absolute error and the relative Frobenius error of a result against a reference
+fn compute_errors(result: &Array2<f64>, reference: &Array2<f64>) -> (f64, f64) {
+    let mut max_abs_error = 0f64;
+    let mut squared_error_sum = 0f64;
+    let mut squared_reference_sum = 0f64;
+
+    for (value, reference_value) in result.iter().zip(reference.iter()) {
+        let error = f64::abs(value - reference_value);
+        max_abs_error = max_abs_error.max(error);
+        squared_error_sum += error * error;
+        squared_reference_sum += reference_value * reference_value;
+    }
+
+    let relative_frobenius_error = if squared_reference_sum > 0.0 {
+        f64::sqrt(squared_error_sum / squared_reference_sum)
+    } else {
+        f64::sqrt(squared_error_sum)
+    };
+
+    (max_abs_error, relative_frobenius_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_sizes_other_than_powers_of_two() {
+        let matrix = Array2::from_shape_fn((3, 3), |(i, j)| (i * 3 + j) as f64);
+        assert!(compute_error_report(&matrix, &matrix, 1).is_err());
+    }
+
+    #[test]
+    fn rejects_non_square_matrices() {
+        let matrix = Array2::from_shape_fn((2, 4), |(i, j)| (i * 4 + j) as f64);
+        assert!(compute_error_report(&matrix, &matrix, 1).is_err());
+    }
+
+    #[test]
+    fn reports_every_recursion_depth() {
+        let matrix_1 = Array2::from_shape_fn((4, 4), |(i, j)| (i * 4 + j) as f64 / 7.0);
+        let matrix_2 = Array2::from_shape_fn((4, 4), |(i, j)| (i as f64 - j as f64) / 3.0);
+
+        let rows = compute_error_report(&matrix_1, &matrix_2, 1).unwrap();
+        let configurations: Vec<_> = rows.iter().map(|row| (row.algorithm_name, row.threshold, row.depth)).collect();
+        assert_eq!(configurations, vec![("conv", None, 0), ("strassenSeuil", Some(2), 1), ("strassen", Some(1), 2)]);
+        for row in rows {
+            assert!(row.relative_frobenius_error < 1e-12);
+        }
+    }
+}
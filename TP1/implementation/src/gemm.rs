@@ -0,0 +1,122 @@
+use std::error::Error;
+use std::str::FromStr;
+use std::time::Instant;
+
+use ndarray::{Array2, ArrayView2};
+use structopt::clap::{Error as ClapError, ErrorKind};
+
+use crate::cli_args::{Algorithm, GemmArgs, ScalarType};
+use crate::matrix_element::{BinaryElement, MatrixElement};
+use crate::matrix_utils::{load_matrix, print_matrix, save_matrix};
+use crate::modular_integer::{set_modulus, ModularInteger};
+use crate::multiplication_algorithms::multiply_matrices;
+
+pub fn run_gemm(args: &GemmArgs) {
+    match args.scalar_type() {
+        ScalarType::Integer => run_gemm_with_type::<i32>(args),
+        ScalarType::Float => run_gemm_with_type::<f64>(args),
+        ScalarType::Modular => {
+            set_modulus(args.modulus).expect("Invalid modulus");
+            run_gemm_with_type::<ModularInteger>(args)
+        }
+    }
+}
+
+fn run_gemm_with_type<T>(args: &GemmArgs)
+where
+    T: MatrixElement + BinaryElement + FromStr,
+    T::Err: Error + 'static,
+{
+    // Parse scalars, where beta defaults to 1 when accumulating into an existing matrix
+    let alpha: T = args.alpha.parse().expect("Error parsing alpha");
+    let beta: T = match &args.beta {
+        Some(beta) => beta.parse().expect("Error parsing beta"),
+        None if args.matrix_3_filename.is_some() => T::one(),
+        None => T::zero(),
+    };
+    if args.matrix_3_filename.is_none() && !beta.is_zero() {
+        ClapError::with_description(
+            "The following required argument was not provided: --c <matrix-3-filename> (beta is not zero)",
+            ErrorKind::MissingRequiredArgument,
+        )
+        .exit()
+    }
+
+    // Load matrices
+    let matrix_1: Array2<T> = load_matrix(&args.matrix_1_filename).expect("Error parsing matrix 1 from file");
+    let matrix_2: Array2<T> = load_matrix(&args.matrix_2_filename).expect("Error parsing matrix 2 from file");
+    let mut matrix_3: Array2<T> = match &args.matrix_3_filename {
+        Some(filename) => load_matrix(filename).expect("Error parsing matrix C from file"),
+        None => {
+            let row_count = if args.transpose_1 { matrix_1.ncols() } else { matrix_1.nrows() };
+            let column_count = if args.transpose_2 { matrix_2.nrows() } else { matrix_2.ncols() };
+            Array2::zeros((row_count, column_count))
+        }
+    };
+
+    // Start clock
+    let now = Instant::now();
+
+    gemm(
+        args.algorithm,
+        args.threshold,
+        alpha,
+        matrix_1.view(),
+        args.transpose_1,
+        matrix_2.view(),
+        args.transpose_2,
+        beta,
+        &mut matrix_3,
+    );
+
+    // Calculate elapsed time
+    let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
+
+    if args.show_result {
+        print_matrix(&matrix_3);
+    }
+
+    if let Some(output_filename) = &args.output_filename {
+        save_matrix(&matrix_3, output_filename, args.output_format).expect("Error writing resulting matrix");
+    }
+
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
+    }
+}
+
+/// Computes C = alpha * op(A) * op(B) + beta * C, where op transposes its matrix if the matching flag is set.
+/// Transposes are views, so no matrix is copied. As in BLAS, C is not read when beta is zero.
+#[allow(clippy::too_many_arguments)]
+pub fn gemm<T: MatrixElement>(
+    algorithm: Algorithm,
+    threshold: usize,
+    alpha: T,
+    matrix_1: ArrayView2<T>,
+    transpose_1: bool,
+    matrix_2: ArrayView2<T>,
+    transpose_2: bool,
+    beta: T,
+    matrix_3: &mut Array2<T>,
+) {
+    let matrix_1 = if transpose_1 { matrix_1.reversed_axes() } else { matrix_1 };
+    let matrix_2 = if transpose_2 { matrix_2.reversed_axes() } else { matrix_2 };
+
+    assert_eq!(matrix_1.ncols(), matrix_2.nrows(), "Incompatible shapes between op(A) and op(B)");
+    assert_eq!(matrix_3.dim(), (matrix_1.nrows(), matrix_2.ncols()), "Incompatible shape of C with op(A) * op(B)");
+    if algorithm != Algorithm::Conventional {
+        let n = matrix_1.nrows();
+        assert!(
+            matrix_1.is_square() && matrix_2.is_square() && n == matrix_2.nrows() && n.is_power_of_two(),
+            "Strassen requires square matrices of the same power of two size"
+        );
+    }
+
+    let product = multiply_matrices(algorithm, &matrix_1, &matrix_2, threshold);
+
+    if beta.is_zero() {
+        matrix_3.zip_mut_with(&product, |c, &p| *c = alpha * p);
+    } else {
+        matrix_3.zip_mut_with(&product, |c, &p| *c = alpha * p + beta * *c);
+    }
+}
@@ -0,0 +1,370 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::cli_args::{Algorithm, Cli};
+
+const WORD_BITS: usize = 64;
+
+/// Number of rows of B combined in each table of the Method of Four Russians
+const FOUR_RUSSIANS_BITS: usize = 8;
+
+pub fn run_gf2_multiplication(args: &Cli) {
+    let (algorithm, matrix_1_filename, matrix_2_filename) = args.multiplication_args();
+    args.require_no_trace("gf2");
+    let output_filename = args.text_output_filename("gf2");
+
+    // Load matrices
+    let matrix_1 = BitMatrix::load_text(matrix_1_filename).expect("Error parsing matrix 1 from file");
+    let matrix_2 = BitMatrix::load_text(matrix_2_filename).expect("Error parsing matrix 2 from file");
+
+    assert_eq!(matrix_1.dim(), matrix_2.dim(), "Incompatible shapes between matrices");
+
+    // Start clock
+    let now = Instant::now();
+
+    // Execute selected algorithm
+    let result = match algorithm {
+        Algorithm::Conventional => multiply_four_russians(&matrix_1, &matrix_2),
+        Algorithm::Strassen => multiply_strassen(&matrix_1, &matrix_2, 0),
+        Algorithm::StrassenThreshold => multiply_strassen(&matrix_1, &matrix_2, args.threshold),
+    };
+
+    // Calculate elapsed time
+    let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
+
+    if args.show_result {
+        let stdout = io::stdout();
+        let mut writer = BufWriter::new(stdout.lock());
+        result.write_rows(&mut writer).and_then(|_| writer.flush()).expect("Error printing matrix");
+    }
+
+    if let Some(output_filename) = output_filename {
+        result.save_text(output_filename).expect("Error writing resulting matrix");
+    }
+
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
+    }
+}
+
+/// Matrix over GF(2) with 64 entries packed in each word. Column j of a row is bit j % 64 of its word j / 64,
+/// and the unused bits of the last word of each row are always zero.
+#[derive(Clone)]
+pub struct BitMatrix {
+    row_count: usize,
+    column_count: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn zeros(row_count: usize, column_count: usize) -> Self {
+        let words_per_row = column_count.div_ceil(WORD_BITS);
+        BitMatrix {
+            row_count,
+            column_count,
+            words_per_row,
+            words: vec![0; row_count * words_per_row],
+        }
+    }
+
+    pub fn dim(&self) -> (usize, usize) {
+        (self.row_count, self.column_count)
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> bool {
+        (self.row(i)[j / WORD_BITS] >> (j % WORD_BITS)) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, value: bool) {
+        let word = &mut self.row_mut(i)[j / WORD_BITS];
+        let mask = 1 << (j % WORD_BITS);
+        if value {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+    }
+
+    fn row(&self, i: usize) -> &[u64] {
+        &self.words[i * self.words_per_row..(i + 1) * self.words_per_row]
+    }
+
+    fn row_mut(&mut self, i: usize) -> &mut [u64] {
+        &mut self.words[i * self.words_per_row..(i + 1) * self.words_per_row]
+    }
+
+    /// Loads a matrix from the text input format, whose entries must all be 0 or 1
+    pub fn load_text(filename: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut lines_it = BufReader::new(File::open(filename)?).lines();
+
+        // Read matrix size
+        let first_line = lines_it.next().ok_or("Empty matrix file")??;
+        let matrix_size = usize::pow(2, first_line.trim().parse()?);
+
+        // Read matrix
+        let mut matrix = BitMatrix::zeros(matrix_size, matrix_size);
+        let mut row_count = 0;
+        for (i, line) in lines_it.enumerate() {
+            if i >= matrix_size {
+                return Err(format!("Matrix has more than {} rows", matrix_size).into());
+            }
+            let line = line?;
+            let column_count = line.split_whitespace().count();
+            if column_count != matrix_size {
+                return Err(format!("Row {} has {} values instead of {}", i, column_count, matrix_size).into());
+            }
+            row_count += 1;
+
+            for (j, number) in line.split_whitespace().enumerate() {
+                match number {
+                    "0" => {}
+                    "1" => matrix.set(i, j, true),
+                    _ => return Err(format!("GF(2) matrices can only contain 0 and 1, got {}", number).into()),
+                }
+            }
+        }
+        if row_count != matrix_size {
+            return Err(format!("Matrix has {} rows instead of {}", row_count, matrix_size).into());
+        }
+
+        Ok(matrix)
+    }
+
+    /// Same format as the input files: the power of two of the matrix size, followed by the space-separated rows
+    pub fn save_text(&self, filename: &Path) -> io::Result<()> {
+        let mut writer: Box<dyn Write> = if filename == Path::new("-") {
+            Box::new(BufWriter::new(io::stdout()))
+        } else {
+            Box::new(BufWriter::new(File::create(filename)?))
+        };
+
+        writeln!(writer, "{}", self.row_count.trailing_zeros())?;
+        self.write_rows(&mut writer)?;
+        writer.flush()
+    }
+
+    fn write_rows<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut line = Vec::with_capacity(2 * self.column_count);
+        for i in 0..self.row_count {
+            line.clear();
+            for j in 0..self.column_count {
+                if j > 0 {
+                    line.push(b' ');
+                }
+                line.push(if self.get(i, j) { b'1' } else { b'0' });
+            }
+            line.push(b'\n');
+            writer.write_all(&line)?;
+        }
+        Ok(())
+    }
+
+    /// Sum of two matrices, which is also their difference in GF(2)
+    pub fn xor(&self, other: &BitMatrix) -> BitMatrix {
+        assert_eq!(self.dim(), other.dim(), "Incompatible shapes between matrices");
+        let mut result = self.clone();
+        for (word, other_word) in result.words.iter_mut().zip(&other.words) {
+            *word ^= other_word;
+        }
+        result
+    }
+
+    /// Copies the row_count x column_count block starting at (first_row, first_column)
+    fn submatrix(&self, first_row: usize, first_column: usize, row_count: usize, column_count: usize) -> BitMatrix {
+        let mut result = BitMatrix::zeros(row_count, column_count);
+
+        if first_column.is_multiple_of(WORD_BITS) {
+            // Whole words can be copied, then the bits past the last column are cleared
+            let first_word = first_column / WORD_BITS;
+            let unused_bits = result.words_per_row * WORD_BITS - column_count;
+            for i in 0..row_count {
+                let source = &self.row(first_row + i)[first_word..first_word + result.words_per_row];
+                let destination = result.row_mut(i);
+                destination.copy_from_slice(source);
+                if let Some(last_word) = destination.last_mut() {
+                    *last_word &= u64::MAX >> unused_bits;
+                }
+            }
+        } else {
+            for i in 0..row_count {
+                for j in 0..column_count {
+                    result.set(i, j, self.get(first_row + i, first_column + j));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Writes a block into a zero region of the matrix, starting at (first_row, first_column)
+    fn insert_submatrix(&mut self, first_row: usize, first_column: usize, block: &BitMatrix) {
+        if first_column.is_multiple_of(WORD_BITS) {
+            let first_word = first_column / WORD_BITS;
+            for i in 0..block.row_count {
+                let destination = &mut self.row_mut(first_row + i)[first_word..first_word + block.words_per_row];
+                for (word, block_word) in destination.iter_mut().zip(block.row(i)) {
+                    *word |= block_word;
+                }
+            }
+        } else {
+            for i in 0..block.row_count {
+                for j in 0..block.column_count {
+                    self.set(first_row + i, first_column + j, block.get(i, j));
+                }
+            }
+        }
+    }
+}
+
+/// Method of Four Russians: rows of B are grouped by FOUR_RUSSIANS_BITS, every XOR combination of a group is
+/// tabulated, and the matching bits of each row of A select the combination added to its row of C
+pub fn multiply_four_russians(matrix_1: &BitMatrix, matrix_2: &BitMatrix) -> BitMatrix {
+    assert_eq!(matrix_1.column_count, matrix_2.row_count, "Incompatible shapes between matrices");
+
+    let mut result = BitMatrix::zeros(matrix_1.row_count, matrix_2.column_count);
+    let words_per_row = matrix_2.words_per_row;
+    let mut table = vec![0u64; (1 << FOUR_RUSSIANS_BITS) * words_per_row];
+
+    for group_start in (0..matrix_1.column_count).step_by(FOUR_RUSSIANS_BITS) {
+        let group_size = usize::min(FOUR_RUSSIANS_BITS, matrix_1.column_count - group_start);
+
+        // Each combination is a previous one, without its lowest bit, plus the row of that bit
+        for combination in 1..1usize << group_size {
+            let (previous_entries, entry) = table.split_at_mut(combination * words_per_row);
+            let previous_entry = &previous_entries[(combination & (combination - 1)) * words_per_row..];
+            let row = matrix_2.row(group_start + combination.trailing_zeros() as usize);
+            for word in 0..words_per_row {
+                entry[word] = previous_entry[word] ^ row[word];
+            }
+        }
+
+        // Groups never straddle words, since the word size is a multiple of the group size
+        let word_index = group_start / WORD_BITS;
+        let shift = group_start % WORD_BITS;
+        let mask = (1 << group_size) - 1;
+        for i in 0..matrix_1.row_count {
+            let combination = ((matrix_1.row(i)[word_index] >> shift) & mask) as usize;
+            if combination == 0 {
+                continue;
+            }
+
+            let entry = &table[combination * words_per_row..(combination + 1) * words_per_row];
+            for (word, entry_word) in result.row_mut(i).iter_mut().zip(entry) {
+                *word ^= entry_word;
+            }
+        }
+    }
+
+    result
+}
+
+/// Strassen over GF(2), where additions and subtractions are XORs, down to the Method of Four Russians at the threshold
+pub fn multiply_strassen(matrix_1: &BitMatrix, matrix_2: &BitMatrix, threshold: usize) -> BitMatrix {
+    let n = matrix_1.row_count;
+
+    if n == 1 || n <= threshold {
+        return multiply_four_russians(matrix_1, matrix_2);
+    }
+
+    let half = n / 2;
+    let quadrants = |matrix: &BitMatrix| {
+        [
+            matrix.submatrix(0, 0, half, half),       // 1,1
+            matrix.submatrix(0, half, half, half),    // 1,2
+            matrix.submatrix(half, 0, half, half),    // 2,1
+            matrix.submatrix(half, half, half, half), // 2,2
+        ]
+    };
+    let matrix_1_quadrants = quadrants(matrix_1);
+    let matrix_2_quadrants = quadrants(matrix_2);
+
+    // M
+    let intermediate_matrices = [
+        multiply_strassen(&matrix_1_quadrants[0].xor(&matrix_1_quadrants[3]), &matrix_2_quadrants[0].xor(&matrix_2_quadrants[3]), threshold), // M1 = (A1,1 + A2,2) * (B1,1 + B2,2)
+        multiply_strassen(&matrix_1_quadrants[2].xor(&matrix_1_quadrants[3]), &matrix_2_quadrants[0], threshold),                             // M2 = (A2,1 + A2,2) * B1,1
+        multiply_strassen(&matrix_1_quadrants[0], &matrix_2_quadrants[1].xor(&matrix_2_quadrants[3]), threshold),                             // M3 = A1,1 * (B1,2 + B2,2)
+        multiply_strassen(&matrix_1_quadrants[3], &matrix_2_quadrants[2].xor(&matrix_2_quadrants[0]), threshold),                             // M4 = A2,2 * (B2,1 + B1,1)
+        multiply_strassen(&matrix_1_quadrants[0].xor(&matrix_1_quadrants[1]), &matrix_2_quadrants[3], threshold),                             // M5 = (A1,1 + A1,2) * B2,2
+        multiply_strassen(&matrix_1_quadrants[2].xor(&matrix_1_quadrants[0]), &matrix_2_quadrants[0].xor(&matrix_2_quadrants[1]), threshold), // M6 = (A2,1 + A1,1) * (B1,1 + B1,2)
+        multiply_strassen(&matrix_1_quadrants[1].xor(&matrix_1_quadrants[3]), &matrix_2_quadrants[2].xor(&matrix_2_quadrants[3]), threshold), // M7 = (A1,2 + A2,2) * (B2,1 + B2,2)
+    ];
+
+    // C
+    let result_quadrants = [
+        intermediate_matrices[0].xor(&intermediate_matrices[3]).xor(&intermediate_matrices[4]).xor(&intermediate_matrices[6]), // C1,1 = M1 + M4 + M5 + M7
+        intermediate_matrices[2].xor(&intermediate_matrices[4]),                                                               // C1,2 = M3 + M5
+        intermediate_matrices[1].xor(&intermediate_matrices[3]),                                                               // C2,1 = M2 + M4
+        intermediate_matrices[0].xor(&intermediate_matrices[1]).xor(&intermediate_matrices[2]).xor(&intermediate_matrices[5]), // C2,2 = M1 + M2 + M3 + M6
+    ];
+
+    let mut result = BitMatrix::zeros(n, n);
+    result.insert_submatrix(0, 0, &result_quadrants[0]);
+    result.insert_submatrix(0, half, &result_quadrants[1]);
+    result.insert_submatrix(half, 0, &result_quadrants[2]);
+    result.insert_submatrix(half, half, &result_quadrants[3]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    fn random_bit_matrix(rng: &mut StdRng, n: usize) -> BitMatrix {
+        let mut matrix = BitMatrix::zeros(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                matrix.set(i, j, rng.gen());
+            }
+        }
+        matrix
+    }
+
+    fn multiply_naive(matrix_1: &BitMatrix, matrix_2: &BitMatrix) -> BitMatrix {
+        let n = matrix_1.row_count;
+        let mut result = BitMatrix::zeros(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                let value = (0..n).fold(false, |value, k| value ^ (matrix_1.get(i, k) & matrix_2.get(k, j)));
+                result.set(i, j, value);
+            }
+        }
+        result
+    }
+
+    fn assert_same_entries(matrix: &BitMatrix, expected: &BitMatrix) {
+        assert_eq!(matrix.dim(), expected.dim());
+        assert!(matrix.words == expected.words, "Product differs from the naive product");
+    }
+
+    #[test]
+    fn four_russians_matches_naive_product() {
+        // Sizes that are not multiples of the group size nor of the word size
+        let mut rng = StdRng::seed_from_u64(0);
+        for &n in &[1, 7, 64, 100, 130] {
+            let matrix_1 = random_bit_matrix(&mut rng, n);
+            let matrix_2 = random_bit_matrix(&mut rng, n);
+            assert_same_entries(&multiply_four_russians(&matrix_1, &matrix_2), &multiply_naive(&matrix_1, &matrix_2));
+        }
+    }
+
+    #[test]
+    fn strassen_matches_naive_product() {
+        // Thresholds below the word size split rows inside words
+        let mut rng = StdRng::seed_from_u64(1);
+        for &n in &[2, 16, 128] {
+            let matrix_1 = random_bit_matrix(&mut rng, n);
+            let matrix_2 = random_bit_matrix(&mut rng, n);
+            let expected = multiply_naive(&matrix_1, &matrix_2);
+            for &threshold in &[0, 8, 64] {
+                assert_same_entries(&multiply_strassen(&matrix_1, &matrix_2, threshold), &expected);
+            }
+        }
+    }
+}
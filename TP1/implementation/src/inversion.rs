@@ -0,0 +1,279 @@
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
+use std::time::Instant;
+
+use ndarray::{concatenate, s, Array2, Axis};
+
+use crate::cli_args::{FieldType, InversionArgs};
+use crate::matrix_element::{BinaryElement, FieldElement};
+use crate::matrix_utils::{load_matrix, print_matrix, save_matrix};
+use crate::modular_integer::{set_modulus, ModularInteger};
+use crate::multiplication_algorithms::multiply_matrices_strassen_threshold;
+
+#[derive(Debug)]
+pub struct SingularMatrixError;
+
+impl fmt::Display for SingularMatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Matrix is singular and cannot be inverted")
+    }
+}
+
+impl Error for SingularMatrixError {}
+
+pub fn run_inversion(args: &InversionArgs) {
+    match args.field_type() {
+        FieldType::Float => run_inversion_with_type::<f64>(args),
+        FieldType::Modular => {
+            set_modulus(args.modulus).expect("Invalid modulus");
+            run_inversion_with_type::<ModularInteger>(args)
+        }
+    }
+}
+
+pub fn run_determinant(args: &InversionArgs) {
+    match args.field_type() {
+        FieldType::Float => run_determinant_with_type::<f64>(args),
+        FieldType::Modular => {
+            set_modulus(args.modulus).expect("Invalid modulus");
+            run_determinant_with_type::<ModularInteger>(args)
+        }
+    }
+}
+
+fn run_inversion_with_type<T>(args: &InversionArgs)
+where
+    T: FieldElement + BinaryElement + FromStr,
+    T::Err: Error + 'static,
+{
+    let matrix: Array2<T> = load_square_matrix(&args.matrix_filename);
+
+    let now = Instant::now();
+    let inverse = invert_matrix(&matrix, args.threshold).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
+
+    if args.show_result {
+        print_matrix(&inverse);
+    }
+
+    if let Some(output_filename) = &args.output_filename {
+        save_matrix(&inverse, output_filename, args.output_format).expect("Error writing inverse matrix");
+    }
+
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
+    }
+}
+
+fn run_determinant_with_type<T>(args: &InversionArgs)
+where
+    T: FieldElement + BinaryElement + FromStr,
+    T::Err: Error + 'static,
+{
+    let matrix: Array2<T> = load_square_matrix(&args.matrix_filename);
+
+    let now = Instant::now();
+    let determinant = compute_determinant(&matrix);
+    let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
+
+    if args.show_result {
+        println!("{}", determinant);
+    }
+
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
+    }
+}
+
+/// Loads a matrix, exiting with an error if it is not square
+fn load_square_matrix<T>(filename: &Path) -> Array2<T>
+where
+    T: FieldElement + BinaryElement + FromStr,
+    T::Err: Error + 'static,
+{
+    let matrix: Array2<T> = load_matrix(filename).expect("Error parsing matrix from file");
+    if !matrix.is_square() {
+        eprintln!("Matrix must be square, got {}x{}", matrix.nrows(), matrix.ncols());
+        process::exit(1);
+    }
+    matrix
+}
+
+pub fn invert_matrix<T: FieldElement>(matrix: &Array2<T>, threshold: usize) -> Result<Array2<T>, SingularMatrixError> {
+    let tolerance = compute_pivot_tolerance(matrix);
+
+    match invert_matrix_blockwise(matrix, threshold, tolerance) {
+        Some(inverse) => Ok(inverse),
+        // A singular leading block does not mean that the whole matrix is singular, so retry with pivoting
+        None => invert_matrix_gauss_jordan(matrix, tolerance).ok_or(SingularMatrixError),
+    }
+}
+
+/// Recursive block inversion using the Schur complement of the leading block, with Strassen for every product.
+/// The matrix is padded to a power of two size with an identity block, so that every block splits in equal halves.
+/// Returns None if a leading block or a Schur complement is singular.
+pub fn invert_matrix_blockwise<T: FieldElement>(matrix: &Array2<T>, threshold: usize, tolerance: f64) -> Option<Array2<T>> {
+    let n = matrix.shape()[0];
+    let padded_size = n.next_power_of_two();
+    if padded_size == n {
+        return invert_padded_matrix_blockwise(matrix, threshold, tolerance);
+    }
+
+    // The inverse of [[A, 0], [0, I]] is [[A^-1, 0], [0, I]]
+    let mut padded_matrix = Array2::eye(padded_size);
+    padded_matrix.slice_mut(s![0..n, 0..n]).assign(matrix);
+    let padded_inverse = invert_padded_matrix_blockwise(&padded_matrix, threshold, tolerance)?;
+    Some(padded_inverse.slice(s![0..n, 0..n]).to_owned())
+}
+
+fn invert_padded_matrix_blockwise<T: FieldElement>(matrix: &Array2<T>, threshold: usize, tolerance: f64) -> Option<Array2<T>> {
+    let n = matrix.shape()[0];
+
+    if n <= threshold.max(1) {
+        return invert_matrix_gauss_jordan(matrix, tolerance);
+    }
+
+    let a11 = matrix.slice(s![0..n / 2, 0..n / 2]).to_owned();
+    let a12 = matrix.slice(s![0..n / 2, n / 2..n]).to_owned();
+    let a21 = matrix.slice(s![n / 2..n, 0..n / 2]).to_owned();
+    let a22 = matrix.slice(s![n / 2..n, n / 2..n]).to_owned();
+
+    let a11_inverse = invert_padded_matrix_blockwise(&a11, threshold, tolerance)?;
+    let a11_inverse_a12 = multiply_matrices_strassen_threshold(&a11_inverse, &a12, threshold);
+    let a21_a11_inverse = multiply_matrices_strassen_threshold(&a21, &a11_inverse, threshold);
+
+    // S = A2,2 - A2,1 * A1,1^-1 * A1,2
+    let schur_complement = &a22 - &multiply_matrices_strassen_threshold(&a21, &a11_inverse_a12, threshold);
+    let schur_complement_inverse = invert_padded_matrix_blockwise(&schur_complement, threshold, tolerance)?;
+
+    // C1,2 = -A1,1^-1 * A1,2 * S^-1
+    let c12 = negate(&multiply_matrices_strassen_threshold(&a11_inverse_a12, &schur_complement_inverse, threshold));
+    // C2,1 = -S^-1 * A2,1 * A1,1^-1
+    let c21 = negate(&multiply_matrices_strassen_threshold(&schur_complement_inverse, &a21_a11_inverse, threshold));
+    // C1,1 = A1,1^-1 + A1,1^-1 * A1,2 * S^-1 * A2,1 * A1,1^-1 = A1,1^-1 - A1,1^-1 * A1,2 * C2,1
+    let c11 = &a11_inverse - &multiply_matrices_strassen_threshold(&a11_inverse_a12, &c21, threshold);
+    // C2,2 = S^-1
+    let c22 = schur_complement_inverse;
+
+    Some(concatenate![
+        Axis(0),
+        concatenate![Axis(1), c11, c12],
+        concatenate![Axis(1), c21, c22]
+    ])
+}
+
+/// Gauss-Jordan elimination with partial pivoting, returning None if the matrix is singular
+pub fn invert_matrix_gauss_jordan<T: FieldElement>(matrix: &Array2<T>, tolerance: f64) -> Option<Array2<T>> {
+    let n = matrix.shape()[0];
+    let mut reduced = matrix.clone();
+    let mut inverse = Array2::eye(n);
+
+    for column in 0..n {
+        let pivot_row = find_pivot_row(&reduced, column, tolerance)?;
+        swap_rows(&mut reduced, column, pivot_row);
+        swap_rows(&mut inverse, column, pivot_row);
+
+        // Normalize pivot row
+        let pivot = reduced[[column, column]];
+        for j in 0..n {
+            reduced[[column, j]] = reduced[[column, j]] / pivot;
+            inverse[[column, j]] = inverse[[column, j]] / pivot;
+        }
+
+        // Eliminate column from every other row
+        for i in 0..n {
+            let factor = reduced[[i, column]];
+            if i == column || factor.is_zero() {
+                continue;
+            }
+            for j in 0..n {
+                let reduced_value = reduced[[column, j]];
+                let inverse_value = inverse[[column, j]];
+                reduced[[i, j]] -= factor * reduced_value;
+                inverse[[i, j]] -= factor * inverse_value;
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+/// Determinant computed from the LU decomposition with partial pivoting
+pub fn compute_determinant<T: FieldElement>(matrix: &Array2<T>) -> T {
+    let n = matrix.shape()[0];
+    let tolerance = compute_pivot_tolerance(matrix);
+    let mut lu = matrix.clone();
+    let mut determinant = T::one();
+
+    for column in 0..n {
+        let pivot_row = match find_pivot_row(&lu, column, tolerance) {
+            Some(pivot_row) => pivot_row,
+            None => return T::zero(), // Singular matrix
+        };
+
+        // Each row swap flips the sign of the determinant
+        if pivot_row != column {
+            swap_rows(&mut lu, column, pivot_row);
+            determinant = T::zero() - determinant;
+        }
+
+        let pivot = lu[[column, column]];
+        determinant = determinant * pivot;
+
+        for i in column + 1..n {
+            let factor = lu[[i, column]] / pivot;
+            lu[[i, column]] = factor;
+            for j in column + 1..n {
+                let upper_value = lu[[column, j]];
+                lu[[i, j]] -= factor * upper_value;
+            }
+        }
+    }
+
+    determinant
+}
+
+pub fn compute_pivot_tolerance<T: FieldElement>(matrix: &Array2<T>) -> f64 {
+    let scale = matrix.iter().map(|x| x.magnitude()).fold(0.0, f64::max);
+    T::pivot_tolerance(matrix.shape()[0], scale)
+}
+
+/// Finds the row with the largest magnitude in the column at or below the diagonal, if it is not negligible
+fn find_pivot_row<T: FieldElement>(matrix: &Array2<T>, column: usize, tolerance: f64) -> Option<usize> {
+    let n = matrix.shape()[0];
+    let mut pivot_row = column;
+    let mut pivot_magnitude = matrix[[column, column]].magnitude();
+
+    for i in column + 1..n {
+        let magnitude = matrix[[i, column]].magnitude();
+        if magnitude > pivot_magnitude {
+            pivot_row = i;
+            pivot_magnitude = magnitude;
+        }
+    }
+
+    if pivot_magnitude <= tolerance {
+        return None;
+    }
+
+    Some(pivot_row)
+}
+
+fn swap_rows<T: FieldElement>(matrix: &mut Array2<T>, row_1: usize, row_2: usize) {
+    if row_1 == row_2 {
+        return;
+    }
+    for j in 0..matrix.shape()[1] {
+        matrix.swap([row_1, j], [row_2, j]);
+    }
+}
+
+fn negate<T: FieldElement>(matrix: &Array2<T>) -> Array2<T> {
+    matrix.mapv(|x| T::zero() - x)
+}
@@ -1,113 +1,115 @@
+use std::error::Error;
+use std::str::FromStr;
 use std::time::Instant;
 
-use ndarray::{arr2, Array2, Axis, concatenate, s};
+use ndarray::Array2;
 use structopt::StructOpt;
 
+mod batch;
 mod cli_args;
+mod complex_matrix;
+mod distributed;
+mod error_report;
+mod gemm;
+mod gf2_matrix;
+mod inversion;
+mod matrix_element;
 mod matrix_utils;
-
-use cli_args::{Algorithm, Cli};
-use matrix_utils::{load_matrix, print_matrix};
+mod modular_integer;
+mod multi_modular;
+mod multiplication_algorithms;
+mod selfcheck;
+mod strassen_trace;
+
+use batch::run_batch;
+use cli_args::{Cli, Command, ElementType};
+use complex_matrix::run_complex_multiplication;
+use distributed::{run_distributed, run_worker};
+use error_report::run_error_report;
+use gemm::run_gemm;
+use gf2_matrix::run_gf2_multiplication;
+use inversion::{run_determinant, run_inversion};
+use matrix_element::{BinaryElement, MatrixElement};
+use matrix_utils::{load_matrix, print_matrix, save_matrix};
+use modular_integer::{set_modulus, ModularInteger};
+use multi_modular::run_exact_multiplication;
+use multiplication_algorithms::multiply_matrices;
+use selfcheck::run_selfcheck;
+use strassen_trace::StrassenTracer;
 
 fn main() {
     // Parse args
     let args = Cli::from_args();
 
+    match &args.command {
+        Some(Command::ErrorReport(error_report_args)) => run_error_report(error_report_args),
+        Some(Command::Batch(batch_args)) => run_batch(batch_args),
+        Some(Command::Invert(inversion_args)) => run_inversion(inversion_args),
+        Some(Command::Determinant(inversion_args)) => run_determinant(inversion_args),
+        Some(Command::Gemm(gemm_args)) => run_gemm(gemm_args),
+        Some(Command::Selfcheck(selfcheck_args)) => run_selfcheck(selfcheck_args),
+        Some(Command::Worker(worker_args)) => run_worker(worker_args),
+        Some(Command::Distribute(distribute_args)) => run_distributed(distribute_args),
+        None => match args.element_type {
+            ElementType::Integer => run_multiplication::<i32>(&args),
+            ElementType::Float => run_multiplication::<f64>(&args),
+            ElementType::Modular => {
+                set_modulus(args.modulus).expect("Invalid modulus");
+                run_multiplication::<ModularInteger>(&args)
+            }
+            ElementType::BigInteger => run_exact_multiplication(&args),
+            ElementType::Gf2 => run_gf2_multiplication(&args),
+            ElementType::Complex => run_complex_multiplication(&args),
+        },
+    }
+}
+
+fn run_multiplication<T>(args: &Cli)
+where
+    T: MatrixElement + BinaryElement + FromStr,
+    T::Err: Error + 'static,
+{
+    let (algorithm, matrix_1_filename, matrix_2_filename) = args.multiplication_args();
+    let trace_threshold = args.trace_threshold();
+
     // Load matrices
-    let matrix_1 = load_matrix(&args.matrix_1_filename).expect("Error parsing matrix 1 from file");
-    let matrix_2 = load_matrix(&args.matrix_2_filename).expect("Error parsing matrix 2 from file");
+    let matrix_1: Array2<T> = load_matrix(matrix_1_filename).expect("Error parsing matrix 1 from file");
+    let matrix_2: Array2<T> = load_matrix(matrix_2_filename).expect("Error parsing matrix 2 from file");
 
     assert_eq!(matrix_1.shape(), matrix_2.shape(), "Incompatible shapes between matrices");
+    assert!(
+        matrix_1.is_square() && matrix_1.nrows().is_power_of_two(),
+        "Matrices must be square with a power of two size"
+    );
 
     // Start clock
     let now = Instant::now();
 
-    // Execute selected algorithm
-    let result = match args.algorithm {
-        Algorithm::Conventional => multiply_matrices_conventional(&matrix_1, &matrix_2),
-        Algorithm::Strassen => multiply_matrices_strassen(&matrix_1, &matrix_2),
-        Algorithm::StrassenThreshold => multiply_matrices_strassen_threshold(&matrix_1, &matrix_2, args.threshold),
+    // Execute selected algorithm, recording its recursive calls if a trace is requested
+    let (result, tracer) = match trace_threshold {
+        None => (multiply_matrices(algorithm, &matrix_1, &matrix_2, args.threshold), None),
+        Some(threshold) => {
+            let (result, tracer) = StrassenTracer::trace(&matrix_1, &matrix_2, threshold);
+            (result, Some(tracer))
+        }
     };
 
     // Calculate elapsed time
     let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
 
-    if args.show_result {
-        print_matrix(&result)
-    }
-
-    if args.show_exec_time {
-        println!("{}", elapsed_ms);
+    if let (Some(trace_filename), Some(tracer)) = (&args.trace_filename, tracer) {
+        tracer.save(trace_filename).expect("Error writing trace file");
     }
-}
 
-fn multiply_matrices_conventional(matrix_1: &Array2<i32>, matrix_2: &Array2<i32>) -> Array2<i32> {
-    let n = matrix_1.shape()[0];
-    let mut result = Array2::zeros((n, n));
-
-    for i in 0..n {
-        for j in 0..n {
-            for k in 0..n {
-                result[[i, j]] += matrix_1[[i, k]] * matrix_2[[k, j]];
-            }
-        }
+    if args.show_result {
+        print_matrix(&result)
     }
 
-    result
-}
-
-fn multiply_matrices_strassen(matrix_1: &Array2<i32>, matrix_2: &Array2<i32>) -> Array2<i32> {
-    multiply_matrices_strassen_threshold(matrix_1, matrix_2, 0)
-}
-
-fn multiply_matrices_strassen_threshold(matrix_1: &Array2<i32>, matrix_2: &Array2<i32>, threshold: usize) -> Array2<i32> {
-    let n = matrix_1.shape()[0];
-
-    if n == 1 {
-        return arr2(&[[matrix_1[[0, 0]] * matrix_2[[0, 0]]]]);
+    if let Some(output_filename) = &args.output_filename {
+        save_matrix(&result, output_filename, args.output_format).expect("Error writing resulting matrix");
     }
 
-    if n <= threshold {
-        return multiply_matrices_conventional(matrix_1, matrix_2);
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
     }
-
-    let matrix_1_slices = [
-        matrix_1.slice(s![0..n / 2, 0..n / 2]), // A1,1
-        matrix_1.slice(s![0..n / 2, n / 2..n]), // A1,2
-        matrix_1.slice(s![n / 2..n, 0..n / 2]), // A2,1
-        matrix_1.slice(s![n / 2..n, n / 2..n]), // A2,2
-    ];
-    let matrix_2_slices = [
-        matrix_2.slice(s![0..n / 2, 0..n / 2]), // B1,1
-        matrix_2.slice(s![0..n / 2, n / 2..n]), // B1,2
-        matrix_2.slice(s![n / 2..n, 0..n / 2]), // B2,1
-        matrix_2.slice(s![n / 2..n, n / 2..n]), // B2,2
-    ];
-
-    // M
-    let intermediate_matrices = [
-        multiply_matrices_strassen_threshold(&(&matrix_1_slices[0] + &matrix_1_slices[3]), &(&matrix_2_slices[0] + &matrix_2_slices[3]), threshold), // M1 = (A1,1 + A2,2) * (B1,1 + B2,2)
-        multiply_matrices_strassen_threshold(&(&matrix_1_slices[2] + &matrix_1_slices[3]), &matrix_2_slices[0].to_owned(), threshold),               // M2 = (A2,1 + A2,2) * B1,1
-        multiply_matrices_strassen_threshold(&matrix_1_slices[0].to_owned(), &(&matrix_2_slices[1] - &matrix_2_slices[3]), threshold),               // M3 = A1,1 * (B1,2 - B2,2)
-        multiply_matrices_strassen_threshold(&matrix_1_slices[3].to_owned(), &(&matrix_2_slices[2] - &matrix_2_slices[0]), threshold),               // M4 = A2,2 * (B2,1 - B1,1)
-        multiply_matrices_strassen_threshold(&(&matrix_1_slices[0] + &matrix_1_slices[1]), &matrix_2_slices[3].to_owned(), threshold),               // M5 = (A1,1 + A1,2) * B2,2
-        multiply_matrices_strassen_threshold(&(&matrix_1_slices[2] - &matrix_1_slices[0]), &(&matrix_2_slices[0] + &matrix_2_slices[1]), threshold), // M6 = (A2,1 - A1,1) * (B1,1 + B1,2)
-        multiply_matrices_strassen_threshold(&(&matrix_1_slices[1] - &matrix_1_slices[3]), &(&matrix_2_slices[2] + &matrix_2_slices[3]), threshold), // M7 = (A1,2 - A2,2) * (B2,1 + B2,2)
-    ];
-
-    // C
-    let result_quadrants = [
-        &intermediate_matrices[0] + &intermediate_matrices[3] - &intermediate_matrices[4] + &intermediate_matrices[6], // C1,1 = M1 + M4 - M5 + M7
-        &intermediate_matrices[2] + &intermediate_matrices[4],                                                         // C1,2 = M3 + M5
-        &intermediate_matrices[1] + &intermediate_matrices[3],                                                         // C2,1 = M2 + M4
-        &intermediate_matrices[0] - &intermediate_matrices[1] + &intermediate_matrices[2] + &intermediate_matrices[5], // C2,2 = M1 - M2 + M3 + M6
-    ];
-
-    let concatenated_result = concatenate![
-        Axis(0),
-        concatenate![Axis(1), result_quadrants[0], result_quadrants[1]],
-        concatenate![Axis(1), result_quadrants[2], result_quadrants[3]]
-    ];
-
-    concatenated_result
 }
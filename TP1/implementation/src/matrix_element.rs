@@ -0,0 +1,94 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::io::{self, Write};
+use std::ops::{AddAssign, Div, SubAssign};
+
+use ndarray::LinalgScalar;
+use num_complex::Complex64;
+
+/// Scalar type that can be stored in a matrix and multiplied by the TP1 algorithms
+pub trait MatrixElement: LinalgScalar + AddAssign + SubAssign + Debug + Display + Send + Sync {}
+
+impl MatrixElement for i32 {}
+impl MatrixElement for f64 {}
+impl MatrixElement for Complex64 {}
+
+/// Matrix element supporting division, as required by inversion and LU decomposition
+pub trait FieldElement: MatrixElement + Div<Output = Self> {
+    /// Size of the value, used to choose pivots
+    fn magnitude(&self) -> f64;
+
+    /// Largest pivot magnitude considered to be zero in an n x n matrix whose largest magnitude is scale
+    fn pivot_tolerance(n: usize, scale: f64) -> f64;
+}
+
+impl FieldElement for f64 {
+    fn magnitude(&self) -> f64 {
+        self.abs()
+    }
+
+    fn pivot_tolerance(n: usize, scale: f64) -> f64 {
+        n as f64 * f64::EPSILON * scale
+    }
+}
+
+/// Matrix element with a fixed-size little-endian representation, used by the binary, NumPy and Matrix Market formats
+pub trait BinaryElement: Sized {
+    /// Type code stored in the header of the binary format
+    const BINARY_TYPE_CODE: u8;
+
+    /// NumPy dtype description
+    const NPY_DESCR: &'static str;
+
+    /// Matrix Market field name
+    const MATRIX_MARKET_FIELD: &'static str;
+
+    /// Name of the element type on the command line
+    const TYPE_NAME: &'static str;
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// Converts an integer read from a typed file, if it can be represented exactly
+    fn from_integer(value: i128) -> Option<Self>;
+
+    /// Converts a floating-point value read from a typed file, if the element type can hold it
+    fn from_float(value: f64) -> Option<Self>;
+}
+
+impl BinaryElement for i32 {
+    const BINARY_TYPE_CODE: u8 = 1;
+    const NPY_DESCR: &'static str = "<i4";
+    const MATRIX_MARKET_FIELD: &'static str = "integer";
+    const TYPE_NAME: &'static str = "i32";
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+
+    fn from_integer(value: i128) -> Option<Self> {
+        i32::try_from(value).ok()
+    }
+
+    fn from_float(_value: f64) -> Option<Self> {
+        None
+    }
+}
+
+impl BinaryElement for f64 {
+    const BINARY_TYPE_CODE: u8 = 2;
+    const NPY_DESCR: &'static str = "<f8";
+    const MATRIX_MARKET_FIELD: &'static str = "real";
+    const TYPE_NAME: &'static str = "f64";
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+
+    fn from_integer(value: i128) -> Option<Self> {
+        Some(value as f64)
+    }
+
+    fn from_float(value: f64) -> Option<Self> {
+        Some(value)
+    }
+}
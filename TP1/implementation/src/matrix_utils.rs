@@ -1,20 +1,85 @@
+use std::convert::{TryFrom, TryInto};
 use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt::Display;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::mem;
 use std::path::Path;
+use std::str::FromStr;
 
 use ndarray::Array2;
+use num_traits::Zero;
 
-pub fn load_matrix(filename: &Path) -> Result<Array2<i32>, Box<dyn Error>> {
-    let buffered = BufReader::new(File::open(filename)?);
-    let mut lines_it = buffered.lines().map(|l| l.unwrap());
+use crate::cli_args::MatrixFormat;
+use crate::matrix_element::{BinaryElement, MatrixElement};
+
+/// Magic bytes at the start of the binary matrix format
+pub const BINARY_MAGIC: &[u8; 8] = b"TPMATRIX";
+
+/// Magic bytes at the start of NumPy .npy files
+pub const NPY_MAGIC: &[u8; 6] = b"\x93NUMPY";
+
+/// First line of Matrix Market files
+const MATRIX_MARKET_BANNER: &str = "%%MatrixMarket";
+
+/// Largest number of values preallocated when reading a binary matrix from an input of unknown length, such as a socket
+const MAX_UNBOUNDED_CAPACITY: usize = 1 << 20;
+
+/// Loads a matrix from the text input format, a NumPy .npy file, a Matrix Market .mtx file or the binary format.
+/// The format is detected from the magic bytes at the start of the file, then from the extension.
+pub fn load_matrix<T>(filename: &Path) -> Result<Array2<T>, Box<dyn Error>>
+where
+    T: MatrixElement + BinaryElement + FromStr,
+    T::Err: Error + 'static,
+{
+    let file = File::open(filename)?;
+    let file_length = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+    let start = reader.fill_buf()?;
+
+    if start.starts_with(NPY_MAGIC) {
+        return load_matrix_npy(&mut reader, file_length);
+    }
+    if start.starts_with(BINARY_MAGIC) {
+        return read_matrix_binary(&mut reader, Some(file_length));
+    }
+    if start.starts_with(MATRIX_MARKET_BANNER.as_bytes()) {
+        return load_matrix_market(reader);
+    }
+
+    match guess_format_from_extension(filename) {
+        MatrixFormat::Npy => Err("Invalid .npy file: missing NumPy magic bytes".into()),
+        MatrixFormat::MatrixMarket => Err("Invalid .mtx file: missing %%MatrixMarket banner".into()),
+        MatrixFormat::Binary => Err("Invalid binary matrix file: missing magic bytes".into()),
+        MatrixFormat::Csv => read_matrix_csv(reader),
+        MatrixFormat::Text => read_matrix_text(reader),
+    }
+}
+
+/// Loads a matrix from the text input format only, for element types without a binary representation
+pub fn load_matrix_text<T>(filename: &Path) -> Result<Array2<T>, Box<dyn Error>>
+where
+    T: Zero + Clone + FromStr,
+    T::Err: Error + 'static,
+{
+    read_matrix_text(BufReader::new(File::open(filename)?))
+}
+
+fn read_matrix_text<T, R>(reader: R) -> Result<Array2<T>, Box<dyn Error>>
+where
+    T: Zero + Clone + FromStr,
+    T::Err: Error + 'static,
+    R: BufRead,
+{
+    let mut lines_it = reader.lines().map(|l| l.unwrap());
 
     // Read matrix size
     let first_line = lines_it.next().unwrap();
     let matrix_size = usize::pow(2, first_line.trim().parse()?);
 
     // Read matrix
-    let mut matrix: Array2<i32> = Array2::zeros((matrix_size, matrix_size));
+    let mut matrix: Array2<T> = Array2::zeros((matrix_size, matrix_size));
     for (i, line) in lines_it.enumerate() {
         for (j, number) in line.split(char::is_whitespace).enumerate() {
             matrix[[i, j]] = number.trim().parse()?;
@@ -24,13 +89,515 @@ pub fn load_matrix(filename: &Path) -> Result<Array2<i32>, Box<dyn Error>> {
     Ok(matrix)
 }
 
-pub fn print_matrix(matrix: &Array2<i32>) {
-    let n = matrix.shape()[0];
-    for i in 0..n {
-        let row_str = matrix.row(i)
-            .map(|x| x.to_string())
-            .to_vec()
-            .join(" ");
-        println!("{}", row_str)
+/// Comma-separated rows, as written by save_matrix for .csv files
+fn read_matrix_csv<T, R>(reader: R) -> Result<Array2<T>, Box<dyn Error>>
+where
+    T: Zero + Clone + FromStr,
+    T::Err: Error + 'static,
+    R: BufRead,
+{
+    let mut values = Vec::new();
+    let mut row_count = 0;
+    let mut column_count = 0;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let row = line.split(',').map(|number| number.trim().parse()).collect::<Result<Vec<T>, _>>()?;
+        if row_count > 0 && row.len() != column_count {
+            return Err(format!("Invalid CSV matrix: row {} has {} values instead of {}", row_count + 1, row.len(), column_count).into());
+        }
+        column_count = row.len();
+        row_count += 1;
+        values.extend(row);
+    }
+
+    Ok(Array2::from_shape_vec((row_count, column_count), values)?)
+}
+
+/// Number of values to preallocate for a matrix whose header declares value_count values of value_size bytes. The
+/// capacity is capped by the bytes left in the input, so that a corrupt header cannot trigger a huge allocation.
+fn value_capacity(value_count: usize, value_size: usize, remaining_length: Option<u64>) -> usize {
+    let max_value_count = match remaining_length {
+        Some(remaining_length) => usize::try_from(remaining_length / value_size as u64).unwrap_or(usize::MAX),
+        None => MAX_UNBOUNDED_CAPACITY,
+    };
+    value_count.min(max_value_count)
+}
+
+/// Returns the number of values of a matrix of T, or an error if it overflows or its values cannot fit in memory
+fn checked_value_count<T>(row_count: usize, column_count: usize) -> Result<usize, Box<dyn Error>> {
+    row_count
+        .checked_mul(column_count)
+        .filter(|&value_count| {
+            value_count
+                .checked_mul(mem::size_of::<T>())
+                .is_some_and(|byte_count| byte_count <= isize::MAX as usize)
+        })
+        .ok_or_else(|| format!("Matrix size {}x{} is too large", row_count, column_count).into())
+}
+
+/// Value read from a typed file, before its conversion to the matrix element type
+enum RawValue {
+    Integer(i128),
+    Float(f64),
+}
+
+fn convert_raw_value<T: BinaryElement>(value: RawValue) -> Result<T, Box<dyn Error>> {
+    match value {
+        RawValue::Integer(integer) => T::from_integer(integer)
+            .ok_or_else(|| format!("Value {} is out of range for {} elements", integer, T::TYPE_NAME).into()),
+        RawValue::Float(float) => T::from_float(float)
+            .ok_or_else(|| format!("Floating-point values cannot be loaded as {} elements, use --type f64", T::TYPE_NAME).into()),
+    }
+}
+
+/// NumPy .npy format, versions 1.0 to 3.0, with a 2-D C-order array of booleans, integers or floats
+fn load_matrix_npy<T: BinaryElement, R: Read>(reader: &mut R, file_length: u64) -> Result<Array2<T>, Box<dyn Error>> {
+    let mut preamble = [0u8; 8];
+    reader.read_exact(&mut preamble)?;
+    let major_version = preamble[6];
+
+    let header_length = match major_version {
+        1 => {
+            let mut bytes = [0u8; 2];
+            reader.read_exact(&mut bytes)?;
+            u16::from_le_bytes(bytes) as usize
+        }
+        2 | 3 => {
+            let mut bytes = [0u8; 4];
+            reader.read_exact(&mut bytes)?;
+            u32::from_le_bytes(bytes) as usize
+        }
+        _ => return Err(format!("Unsupported .npy format version: {}", major_version).into()),
+    };
+
+    let header_end = (preamble.len() + if major_version == 1 { 2 } else { 4 } + header_length) as u64;
+    let mut header_bytes = vec![0u8; header_length];
+    reader.read_exact(&mut header_bytes)?;
+    let header = String::from_utf8(header_bytes)?;
+
+    let descr = parse_npy_header_value(&header, "descr")?;
+    let descr = descr.trim_matches(['\'', '"']);
+    let fortran_order = parse_npy_header_value(&header, "fortran_order")?;
+    let shape = parse_npy_header_value(&header, "shape")?;
+
+    if fortran_order != "False" {
+        return Err("Unsupported .npy layout: Fortran-ordered arrays are not supported, save the array in C order".into());
+    }
+
+    let dimensions = shape
+        .trim_matches(['(', ')'])
+        .split(',')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<usize>, _>>()?;
+    if dimensions.len() != 2 {
+        return Err(format!("Unsupported .npy shape {}: expected a 2-D array", shape).into());
+    }
+
+    // Parse dtype, such as "<i4", ">f8" or "|b1"
+    let mut descr_chars = descr.chars();
+    let byte_order = descr_chars.next().ok_or("Empty .npy dtype")?;
+    let kind = descr_chars.next().ok_or("Invalid .npy dtype")?;
+    let size: usize = descr_chars.as_str().parse().map_err(|_| format!("Unsupported .npy dtype: {}", descr))?;
+    let is_big_endian = match byte_order {
+        '<' | '|' => false,
+        '>' => true,
+        '=' => cfg!(target_endian = "big"),
+        _ => return Err(format!("Unsupported .npy dtype: {}", descr).into()),
+    };
+    match (kind, size) {
+        ('b', 1) | ('i', 1) | ('i', 2) | ('i', 4) | ('i', 8) | ('u', 1) | ('u', 2) | ('u', 4) | ('u', 8) | ('f', 4) | ('f', 8) => (),
+        ('c', _) => return Err(format!("Unsupported .npy dtype {}: complex values are not supported", descr).into()),
+        _ => return Err(format!("Unsupported .npy dtype: {}", descr).into()),
+    }
+
+    let value_count = checked_value_count::<T>(dimensions[0], dimensions[1])?;
+    let mut matrix = Vec::with_capacity(value_capacity(value_count, size, Some(file_length.saturating_sub(header_end))));
+    let mut bytes = vec![0u8; size];
+    for _ in 0..value_count {
+        reader.read_exact(&mut bytes)?;
+        if is_big_endian {
+            bytes.reverse();
+        }
+
+        // Sign-extend or zero-extend the little-endian bytes to 16 bytes
+        let is_negative = kind == 'i' && bytes[size - 1] & 0x80 != 0;
+        let mut extended_bytes = [if is_negative { 0xff } else { 0 }; 16];
+        extended_bytes[..size].copy_from_slice(&bytes);
+
+        let value = match kind {
+            'f' if size == 4 => RawValue::Float(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64),
+            'f' => RawValue::Float(f64::from_le_bytes(extended_bytes[..8].try_into()?)),
+            _ => RawValue::Integer(i128::from_le_bytes(extended_bytes)),
+        };
+        matrix.push(convert_raw_value(value)?);
+    }
+
+    Ok(Array2::from_shape_vec((dimensions[0], dimensions[1]), matrix)?)
+}
+
+/// Returns the raw text of a value in the Python dictionary literal of a .npy header
+fn parse_npy_header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, Box<dyn Error>> {
+    let missing_key_error = || format!("Invalid .npy header: missing key {}", key);
+
+    let key_position = header
+        .find(&format!("'{}'", key))
+        .or_else(|| header.find(&format!("\"{}\"", key)))
+        .ok_or_else(missing_key_error)?;
+    let value_start = key_position + header[key_position..].find(':').ok_or_else(missing_key_error)? + 1;
+    let value = header[value_start..].trim_start();
+
+    // Tuples contain commas, so they end at the closing parenthesis instead of the next comma
+    let value_end = if value.starts_with('(') {
+        value.find(')').map(|i| i + 1)
+    } else {
+        value.find([',', '}'])
+    }
+    .ok_or_else(missing_key_error)?;
+
+    Ok(value[..value_end].trim())
+}
+
+/// Matrix Market array or coordinate format, with integer, real or pattern values
+fn load_matrix_market<T, R>(reader: R) -> Result<Array2<T>, Box<dyn Error>>
+where
+    T: MatrixElement + BinaryElement,
+    R: BufRead,
+{
+    let mut lines_it = reader.lines();
+
+    // Parse banner, such as "%%MatrixMarket matrix coordinate real symmetric"
+    let banner = lines_it.next().ok_or("Empty Matrix Market file")??.to_lowercase();
+    let banner_fields: Vec<_> = banner.split_whitespace().collect();
+    if banner_fields.len() != 5 || banner_fields[1] != "matrix" {
+        return Err(format!("Invalid Matrix Market banner: {}", banner).into());
+    }
+    let (layout, field, symmetry) = (banner_fields[2], banner_fields[3], banner_fields[4]);
+
+    if field == "complex" {
+        return Err("Unsupported Matrix Market field: complex values are not supported".into());
+    }
+    if !["integer", "real", "pattern"].contains(&field) {
+        return Err(format!("Unsupported Matrix Market field: {}", field).into());
+    }
+    if !["general", "symmetric", "skew-symmetric"].contains(&symmetry) {
+        return Err(format!("Unsupported Matrix Market symmetry: {}", symmetry).into());
+    }
+    if layout == "array" && field == "pattern" {
+        return Err("Invalid Matrix Market file: pattern field requires the coordinate layout".into());
+    }
+
+    // Skip comments and blank lines
+    let mut data_lines_it = lines_it.filter(|line| match line {
+        Ok(line) => !line.starts_with('%') && !line.trim().is_empty(),
+        Err(_) => true,
+    });
+
+    let size_line = data_lines_it.next().ok_or("Invalid Matrix Market file: missing size line")??;
+    let sizes = size_line
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    let parse_value = |text: &str| -> Result<T, Box<dyn Error>> {
+        match field {
+            "integer" => convert_raw_value(RawValue::Integer(text.parse()?)),
+            "real" => convert_raw_value(RawValue::Float(text.parse()?)),
+            _ => convert_raw_value(RawValue::Integer(1)),
+        }
+    };
+
+    let (row_count, column_count) = match sizes[..] {
+        [row_count, column_count] if layout == "array" => (row_count, column_count),
+        [row_count, column_count, _] if layout == "coordinate" => (row_count, column_count),
+        _ => return Err(format!("Invalid Matrix Market size line: {}", size_line).into()),
+    };
+    if symmetry != "general" && row_count != column_count {
+        return Err(format!("Invalid Matrix Market file: {} matrices must be square, got {}x{}", symmetry, row_count, column_count).into());
+    }
+    checked_value_count::<T>(row_count, column_count)?;
+    let mut matrix = Array2::zeros((row_count, column_count));
+
+    // Stores a value and its mirrored value for symmetric matrices
+    let mut set_value = |i: usize, j: usize, value: T| -> Result<(), Box<dyn Error>> {
+        if i >= row_count || j >= column_count {
+            return Err(format!("Matrix Market entry ({}, {}) is out of bounds", i + 1, j + 1).into());
+        }
+        if i != j {
+            match symmetry {
+                "symmetric" => matrix[[j, i]] = value,
+                "skew-symmetric" => matrix[[j, i]] = T::zero() - value,
+                _ => (),
+            }
+        }
+        matrix[[i, j]] = value;
+        Ok(())
+    };
+
+    if layout == "array" {
+        // Values are listed in column-major order, and only the lower triangle is listed for symmetric matrices
+        let mut values_it = data_lines_it.map(|line| line.map(|line| line.trim().to_string()));
+        for j in 0..column_count {
+            let first_row = match symmetry {
+                "general" => 0,
+                "symmetric" => j,
+                _ => j + 1,
+            };
+            for i in first_row..row_count {
+                let text = values_it.next().ok_or("Invalid Matrix Market file: missing values")??;
+                set_value(i, j, parse_value(&text)?)?;
+            }
+        }
+    } else {
+        let entry_count = sizes[2];
+        for _ in 0..entry_count {
+            let line = data_lines_it.next().ok_or("Invalid Matrix Market file: missing entries")??;
+            let entry_fields: Vec<_> = line.split_whitespace().collect();
+            if entry_fields.len() < 2 || (field != "pattern" && entry_fields.len() < 3) {
+                return Err(format!("Invalid Matrix Market entry: {}", line).into());
+            }
+
+            // Matrix Market indices are 1-based
+            let i = entry_fields[0].parse::<usize>()?.checked_sub(1).ok_or("Matrix Market indices start at 1")?;
+            let j = entry_fields[1].parse::<usize>()?.checked_sub(1).ok_or("Matrix Market indices start at 1")?;
+            let value = parse_value(entry_fields.get(2).unwrap_or(&""))?;
+            set_value(i, j, value)?;
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// Binary format written by write_matrix_binary, whose values are converted if their type differs from the element type
+pub fn load_matrix_binary<T: BinaryElement, R: Read>(reader: &mut R) -> Result<Array2<T>, Box<dyn Error>> {
+    read_matrix_binary(reader, None)
+}
+
+/// Binary format read from an input whose length is known for files, and unknown for sockets
+fn read_matrix_binary<T, R>(reader: &mut R, input_length: Option<u64>) -> Result<Array2<T>, Box<dyn Error>>
+where
+    T: BinaryElement,
+    R: Read,
+{
+    let mut header = [0u8; 25];
+    reader.read_exact(&mut header)?;
+    if &header[..8] != BINARY_MAGIC {
+        return Err("Invalid binary matrix: missing magic bytes".into());
+    }
+    let type_code = header[8];
+    let row_count = usize::try_from(u64::from_le_bytes(header[9..17].try_into()?))?;
+    let column_count = usize::try_from(u64::from_le_bytes(header[17..25].try_into()?))?;
+
+    let value_size = if type_code == 1 { 4 } else { 8 };
+    let value_count = checked_value_count::<T>(row_count, column_count)?;
+    let remaining_length = input_length.map(|input_length| input_length.saturating_sub(header.len() as u64));
+    let mut matrix = Vec::with_capacity(value_capacity(value_count, value_size, remaining_length));
+    for _ in 0..value_count {
+        let value = match type_code {
+            1 => {
+                let mut bytes = [0u8; 4];
+                reader.read_exact(&mut bytes)?;
+                RawValue::Integer(i32::from_le_bytes(bytes) as i128)
+            }
+            2 => {
+                let mut bytes = [0u8; 8];
+                reader.read_exact(&mut bytes)?;
+                RawValue::Float(f64::from_le_bytes(bytes))
+            }
+            3 => {
+                let mut bytes = [0u8; 8];
+                reader.read_exact(&mut bytes)?;
+                RawValue::Integer(u64::from_le_bytes(bytes) as i128)
+            }
+            _ => return Err(format!("Unsupported binary matrix type code: {}", type_code).into()),
+        };
+        matrix.push(convert_raw_value(value)?);
+    }
+
+    Ok(Array2::from_shape_vec((row_count, column_count), matrix)?)
+}
+
+pub fn print_matrix<T: Display>(matrix: &Array2<T>) {
+    let stdout = io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+    write_rows(matrix, &mut writer, " ")
+        .and_then(|_| writer.flush())
+        .expect("Error printing matrix");
+}
+
+/// Writes a matrix to a file, or to stdout if the filename is "-". The format is guessed from the extension if not given.
+pub fn save_matrix<T>(matrix: &Array2<T>, filename: &Path, format: Option<MatrixFormat>) -> io::Result<()>
+where
+    T: Display + BinaryElement,
+{
+    let format = format.unwrap_or_else(|| guess_format_from_extension(filename));
+
+    if filename == Path::new("-") {
+        let stdout = io::stdout();
+        let mut writer = BufWriter::new(stdout.lock());
+        write_matrix(matrix, &mut writer, format)?;
+        return writer.flush();
+    }
+
+    let mut writer = BufWriter::new(File::create(filename)?);
+    write_matrix(matrix, &mut writer, format)?;
+    writer.flush()
+}
+
+pub fn guess_format_from_extension(filename: &Path) -> MatrixFormat {
+    match filename.extension().and_then(OsStr::to_str) {
+        Some("csv") => MatrixFormat::Csv,
+        Some("npy") => MatrixFormat::Npy,
+        Some("mtx") => MatrixFormat::MatrixMarket,
+        Some("bin") => MatrixFormat::Binary,
+        _ => MatrixFormat::Text,
+    }
+}
+
+/// Writes a matrix in the given format. The writer should be buffered, since values are written one by one.
+pub fn write_matrix<T, W>(matrix: &Array2<T>, writer: &mut W, format: MatrixFormat) -> io::Result<()>
+where
+    T: Display + BinaryElement,
+    W: Write,
+{
+    match format {
+        MatrixFormat::Text => write_matrix_text(matrix, writer),
+        MatrixFormat::Csv => write_rows(matrix, writer, ","),
+        MatrixFormat::Npy => write_matrix_npy(matrix, writer),
+        MatrixFormat::MatrixMarket => write_matrix_market(matrix, writer),
+        MatrixFormat::Binary => write_matrix_binary(matrix, writer),
+    }
+}
+
+fn write_rows<T: Display, W: Write>(matrix: &Array2<T>, writer: &mut W, separator: &str) -> io::Result<()> {
+    for row in matrix.rows() {
+        for (j, value) in row.iter().enumerate() {
+            if j > 0 {
+                writer.write_all(separator.as_bytes())?;
+            }
+            write!(writer, "{}", value)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Same format as the input files: the power of two of the matrix size, followed by the space-separated rows
+fn write_matrix_text<T: Display, W: Write>(matrix: &Array2<T>, writer: &mut W) -> io::Result<()> {
+    let (row_count, column_count) = matrix.dim();
+    if row_count != column_count || !row_count.is_power_of_two() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Text format requires a square matrix with a power of two size, got {}x{}", row_count, column_count),
+        ));
+    }
+
+    writeln!(writer, "{}", row_count.trailing_zeros())?;
+    write_rows(matrix, writer, " ")
+}
+
+/// NumPy .npy format version 1.0, with a C-order array
+fn write_matrix_npy<T: BinaryElement, W: Write>(matrix: &Array2<T>, writer: &mut W) -> io::Result<()> {
+    let (row_count, column_count) = matrix.dim();
+
+    // The header is padded with spaces and terminated by a newline so that the data is 64-byte aligned
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+        T::NPY_DESCR,
+        row_count,
+        column_count
+    );
+    let preamble_length = NPY_MAGIC.len() + 2 + 2;
+    let padding = 63 - (preamble_length + header.len()) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    writer.write_all(NPY_MAGIC)?;
+    writer.write_all(&[1, 0])?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+
+    for value in matrix.iter() {
+        value.write_le(writer)?;
+    }
+    Ok(())
+}
+
+/// Matrix Market dense array format, whose values are listed in column-major order
+fn write_matrix_market<T, W>(matrix: &Array2<T>, writer: &mut W) -> io::Result<()>
+where
+    T: Display + BinaryElement,
+    W: Write,
+{
+    let (row_count, column_count) = matrix.dim();
+
+    writeln!(writer, "%%MatrixMarket matrix array {} general", T::MATRIX_MARKET_FIELD)?;
+    writeln!(writer, "{} {}", row_count, column_count)?;
+    for column in matrix.columns() {
+        for value in column {
+            writeln!(writer, "{}", value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Binary format: magic bytes, element type code, row and column counts as u64, then row-major little-endian values
+pub fn write_matrix_binary<T: BinaryElement, W: Write>(matrix: &Array2<T>, writer: &mut W) -> io::Result<()> {
+    let (row_count, column_count) = matrix.dim();
+
+    writer.write_all(BINARY_MAGIC)?;
+    writer.write_all(&[T::BINARY_TYPE_CODE])?;
+    writer.write_all(&(row_count as u64).to_le_bytes())?;
+    writer.write_all(&(column_count as u64).to_le_bytes())?;
+
+    for value in matrix.iter() {
+        value.write_le(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_matrix_market_text(text: &str) -> Result<Array2<i32>, Box<dyn Error>> {
+        load_matrix_market(text.as_bytes())
+    }
+
+    #[test]
+    fn matrix_market_mirrors_symmetric_entries() {
+        let text = "%%MatrixMarket matrix coordinate integer symmetric\n% comment\n2 2 2\n1 1 3\n2 1 5\n";
+        assert_eq!(load_matrix_market_text(text).unwrap(), ndarray::arr2(&[[3, 5], [5, 0]]));
+    }
+
+    #[test]
+    fn matrix_market_negates_skew_symmetric_entries() {
+        let text = "%%MatrixMarket matrix array integer skew-symmetric\n2 2\n4\n";
+        assert_eq!(load_matrix_market_text(text).unwrap(), ndarray::arr2(&[[0, -4], [4, 0]]));
+    }
+
+    #[test]
+    fn matrix_market_rejects_non_square_symmetric_matrices() {
+        let text = "%%MatrixMarket matrix coordinate integer symmetric\n2 4 1\n1 3 5\n";
+        assert!(load_matrix_market_text(text).is_err());
+        let text = "%%MatrixMarket matrix coordinate integer skew-symmetric\n4 2 1\n3 1 5\n";
+        assert!(load_matrix_market_text(text).is_err());
+    }
+
+    #[test]
+    fn matrix_market_rejects_overflowing_size() {
+        let text = "%%MatrixMarket matrix coordinate integer general\n3000000000 3000000000 0\n";
+        assert!(load_matrix_market_text(text).is_err());
+    }
+
+    #[test]
+    fn matrix_market_rejects_out_of_bounds_entries() {
+        let text = "%%MatrixMarket matrix coordinate integer general\n2 2 1\n3 1 5\n";
+        assert!(load_matrix_market_text(text).is_err());
     }
 }
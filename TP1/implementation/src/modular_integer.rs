@@ -0,0 +1,211 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use num_traits::{One, Zero};
+
+use crate::matrix_element::{BinaryElement, FieldElement, MatrixElement};
+
+/// Modulus shared by every ModularInteger, since ndarray needs elements that can be created without context
+static MODULUS: AtomicU64 = AtomicU64::new(1_000_000_007);
+
+/// Largest supported modulus, so that the sum of two residues cannot overflow a u64
+const MAX_MODULUS: u64 = 1 << 63;
+
+pub fn set_modulus(modulus: u64) -> Result<(), String> {
+    if modulus >= MAX_MODULUS || !is_prime(modulus) {
+        return Err(format!("Modulus must be a prime smaller than 2^63: {}", modulus));
+    }
+    MODULUS.store(modulus, Ordering::Relaxed);
+    Ok(())
+}
+
+pub fn get_modulus() -> u64 {
+    MODULUS.load(Ordering::Relaxed)
+}
+
+/// Integer modulo the prime set with set_modulus
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModularInteger(u64);
+
+impl ModularInteger {
+    pub fn new(value: i128) -> Self {
+        ModularInteger(value.rem_euclid(get_modulus() as i128) as u64)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    pub fn pow(self, exponent: u64) -> Self {
+        ModularInteger(pow_mod(self.0, exponent, get_modulus()))
+    }
+
+    /// Multiplicative inverse, which exists for every non-zero value since the modulus is prime
+    pub fn inverse(self) -> Option<Self> {
+        if self.0 == 0 {
+            return None;
+        }
+        Some(self.pow(get_modulus() - 2))
+    }
+}
+
+impl Add for ModularInteger {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let sum = self.0 + other.0;
+        let modulus = get_modulus();
+        ModularInteger(if sum >= modulus { sum - modulus } else { sum })
+    }
+}
+
+impl Sub for ModularInteger {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        if self.0 >= other.0 {
+            ModularInteger(self.0 - other.0)
+        } else {
+            ModularInteger(self.0 + (get_modulus() - other.0))
+        }
+    }
+}
+
+impl Mul for ModularInteger {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        ModularInteger(multiply_mod(self.0, other.0, get_modulus()))
+    }
+}
+
+impl Div for ModularInteger {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        self.mul(other.inverse().expect("Division by zero modulo the modulus"))
+    }
+}
+
+impl AddAssign for ModularInteger {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for ModularInteger {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Zero for ModularInteger {
+    fn zero() -> Self {
+        ModularInteger(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl One for ModularInteger {
+    fn one() -> Self {
+        ModularInteger(1)
+    }
+}
+
+impl FromStr for ModularInteger {
+    type Err = ParseIntError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ModularInteger::new(s.parse()?))
+    }
+}
+
+impl fmt::Display for ModularInteger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl MatrixElement for ModularInteger {}
+
+impl BinaryElement for ModularInteger {
+    const BINARY_TYPE_CODE: u8 = 3;
+    const NPY_DESCR: &'static str = "<u8";
+    const MATRIX_MARKET_FIELD: &'static str = "integer";
+    const TYPE_NAME: &'static str = "mod";
+
+    fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.0.to_le_bytes())
+    }
+
+    fn from_integer(value: i128) -> Option<Self> {
+        Some(ModularInteger::new(value))
+    }
+
+    fn from_float(_value: f64) -> Option<Self> {
+        None
+    }
+}
+
+impl FieldElement for ModularInteger {
+    fn magnitude(&self) -> f64 {
+        if self.0 == 0 { 0.0 } else { 1.0 }
+    }
+
+    fn pivot_tolerance(_n: usize, _scale: f64) -> f64 {
+        0.0
+    }
+}
+
+/// Deterministic Miller-Rabin primality test for 64-bit integers
+pub fn is_prime(n: u64) -> bool {
+    const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    for &witness in &WITNESSES {
+        if n.is_multiple_of(witness) {
+            return n == witness;
+        }
+    }
+
+    // Write n - 1 as d * 2^s with d odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    'witness_loop: for &witness in &WITNESSES {
+        let mut x = pow_mod(witness, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = multiply_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness_loop;
+            }
+        }
+        return false;
+    }
+
+    true
+}
+
+pub fn multiply_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+pub fn pow_mod(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut result = 1 % modulus;
+    base %= modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply_mod(result, base, modulus);
+        }
+        base = multiply_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
@@ -0,0 +1,186 @@
+use std::time::Instant;
+
+use ndarray::Array2;
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use crate::cli_args::{Algorithm, Cli};
+use crate::matrix_utils::{load_matrix_text, print_matrix};
+use crate::modular_integer::{is_prime, multiply_mod, pow_mod, set_modulus, ModularInteger};
+use crate::multiplication_algorithms::multiply_matrices;
+
+/// Primes are taken below this bound, so that they stay valid moduli for ModularInteger
+const LARGEST_PRIME_BOUND: u64 = 1 << 62;
+
+pub fn run_exact_multiplication(args: &Cli) {
+    let (algorithm, matrix_1_filename, matrix_2_filename) = args.multiplication_args();
+    args.require_no_trace("bigint");
+    args.require_no_output_file("bigint");
+
+    // Load matrices
+    let matrix_1: Array2<BigInt> = load_matrix_text(matrix_1_filename).expect("Error parsing matrix 1 from file");
+    let matrix_2: Array2<BigInt> = load_matrix_text(matrix_2_filename).expect("Error parsing matrix 2 from file");
+
+    assert_eq!(matrix_1.shape(), matrix_2.shape(), "Incompatible shapes between matrices");
+
+    // Start clock
+    let now = Instant::now();
+
+    let result = multiply_matrices_multi_modular(algorithm, &matrix_1, &matrix_2, args.threshold);
+
+    // Calculate elapsed time
+    let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
+
+    if args.show_result {
+        print_matrix(&result)
+    }
+
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
+    }
+}
+
+/// Exact product of integer matrices, computed modulo enough word-sized primes and rebuilt with the Chinese Remainder Theorem
+pub fn multiply_matrices_multi_modular(
+    algorithm: Algorithm,
+    matrix_1: &Array2<BigInt>,
+    matrix_2: &Array2<BigInt>,
+    threshold: usize,
+) -> Array2<BigInt> {
+    let n = matrix_1.shape()[0];
+
+    // Every entry of the product is at most n * max|A| * max|B| in absolute value, and the primes' product must
+    // exceed twice that bound so that negative entries can be recovered
+    let max_magnitude = |matrix: &Array2<BigInt>| matrix.iter().map(|x| x.abs()).max().unwrap_or_else(BigInt::zero);
+    let entry_bound = BigInt::from(n) * max_magnitude(matrix_1) * max_magnitude(matrix_2);
+    let primes = choose_primes(&(entry_bound * 2u32));
+
+    // Multiply modulo each prime
+    let residue_matrices: Vec<Array2<u64>> = primes
+        .iter()
+        .map(|&prime| {
+            set_modulus(prime).unwrap();
+            let reduced_matrix_1 = matrix_1.map(|x| reduce_modulo(x, prime));
+            let reduced_matrix_2 = matrix_2.map(|x| reduce_modulo(x, prime));
+            multiply_matrices(algorithm, &reduced_matrix_1, &reduced_matrix_2, threshold).mapv(ModularInteger::value)
+        })
+        .collect();
+
+    let reconstructor = CrtReconstructor::new(&primes);
+    Array2::from_shape_fn(matrix_1.dim(), |(i, j)| {
+        let residues: Vec<u64> = residue_matrices.iter().map(|residue_matrix| residue_matrix[[i, j]]).collect();
+        reconstructor.reconstruct(&residues)
+    })
+}
+
+/// Returns the largest primes below LARGEST_PRIME_BOUND, until their product exceeds the bound
+fn choose_primes(bound: &BigInt) -> Vec<u64> {
+    let mut primes = Vec::new();
+    let mut product = BigInt::from(1);
+    let mut candidate = LARGEST_PRIME_BOUND - 1;
+
+    while product <= *bound || primes.is_empty() {
+        if is_prime(candidate) {
+            primes.push(candidate);
+            product *= candidate;
+        }
+        candidate -= 2;
+    }
+
+    primes
+}
+
+fn reduce_modulo(value: &BigInt, prime: u64) -> ModularInteger {
+    // The remainder has the sign of the value, which ModularInteger::new brings back into [0, prime)
+    let remainder = value % BigInt::from(prime);
+    ModularInteger::new(remainder.to_i128().unwrap())
+}
+
+/// Garner's algorithm, which rebuilds an integer from its residues in mixed radix representation
+struct CrtReconstructor {
+    primes: Vec<u64>,
+    /// Inverse of the product of the previous primes p0 * ... * p(i-1), modulo prime i
+    prefix_product_inverses: Vec<u64>,
+    /// Product of all primes
+    modulus: BigInt,
+}
+
+impl CrtReconstructor {
+    fn new(primes: &[u64]) -> Self {
+        let mut prefix_product_inverses = Vec::new();
+
+        for (i, &prime) in primes.iter().enumerate() {
+            let prefix_product = primes[..i].iter().fold(1 % prime, |product, &previous_prime| multiply_mod(product, previous_prime, prime));
+            prefix_product_inverses.push(pow_mod(prefix_product, prime - 2, prime));
+        }
+
+        let modulus = primes.iter().fold(BigInt::from(1), |product, &prime| product * prime);
+
+        CrtReconstructor {
+            primes: primes.to_vec(),
+            prefix_product_inverses,
+            modulus,
+        }
+    }
+
+    /// Returns the integer in (-M/2, M/2] congruent to each residue, where M is the product of the primes
+    fn reconstruct(&self, residues: &[u64]) -> BigInt {
+        // Mixed radix digits: x = v0 + v1 * p0 + v2 * p0 * p1 + ...
+        let mut digits: Vec<u64> = Vec::with_capacity(self.primes.len());
+        for (i, &prime) in self.primes.iter().enumerate() {
+            // Evaluate v0 + v1 * p0 + ... + v(i-1) * p0 * ... * p(i-2) modulo prime i with Horner's method
+            let mut partial_value = 0u64;
+            for j in (0..i).rev() {
+                partial_value = (multiply_mod(partial_value, self.primes[j] % prime, prime) + digits[j] % prime) % prime;
+            }
+
+            let difference = (residues[i] % prime + prime - partial_value) % prime;
+            digits.push(multiply_mod(difference, self.prefix_product_inverses[i], prime));
+        }
+
+        let mut value = BigInt::zero();
+        for (i, &digit) in digits.iter().enumerate().rev() {
+            value = value * self.primes[i] + digit;
+        }
+
+        if value.clone() * 2u32 > self.modulus {
+            value -= &self.modulus;
+        }
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    fn multiply_matrices_naive(matrix_1: &Array2<BigInt>, matrix_2: &Array2<BigInt>) -> Array2<BigInt> {
+        let n = matrix_1.shape()[0];
+        Array2::from_shape_fn((n, n), |(i, j)| (0..n).map(|k| &matrix_1[[i, k]] * &matrix_2[[k, j]]).sum())
+    }
+
+    /// Values of about 90 bits of either sign, so that their products need several primes
+    fn random_big_matrix(rng: &mut StdRng, n: usize) -> Array2<BigInt> {
+        Array2::from_shape_simple_fn((n, n), || (BigInt::from(rng.gen::<i32>()) << 64) + rng.gen::<u64>())
+    }
+
+    #[test]
+    fn product_beyond_one_prime_is_exact() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let n = 8;
+        let matrix_1 = random_big_matrix(&mut rng, n);
+        let matrix_2 = random_big_matrix(&mut rng, n);
+        let expected = multiply_matrices_naive(&matrix_1, &matrix_2);
+
+        let largest_entry = expected.iter().map(|x| x.abs()).max().unwrap();
+        assert!(largest_entry > BigInt::from(LARGEST_PRIME_BOUND) * LARGEST_PRIME_BOUND);
+
+        for &algorithm in &Algorithm::ALL {
+            assert_eq!(multiply_matrices_multi_modular(algorithm, &matrix_1, &matrix_2, 2), expected);
+        }
+    }
+}
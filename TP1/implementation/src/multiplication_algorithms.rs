@@ -0,0 +1,187 @@
+use ndarray::{arr2, concatenate, s, Array2, ArrayBase, Axis, CowArray, Data, Ix2};
+
+use crate::cli_args::Algorithm;
+use crate::matrix_element::MatrixElement;
+
+/// Multiplies two matrices, which can be owned arrays or views such as transposes
+pub fn multiply_matrices<T, S1, S2>(
+    algorithm: Algorithm,
+    matrix_1: &ArrayBase<S1, Ix2>,
+    matrix_2: &ArrayBase<S2, Ix2>,
+    threshold: usize,
+) -> Array2<T>
+where
+    T: MatrixElement,
+    S1: Data<Elem = T>,
+    S2: Data<Elem = T>,
+{
+    match algorithm {
+        Algorithm::Conventional => multiply_matrices_conventional(matrix_1, matrix_2),
+        Algorithm::Strassen => multiply_matrices_strassen(matrix_1, matrix_2),
+        Algorithm::StrassenThreshold => multiply_matrices_strassen_threshold(matrix_1, matrix_2, threshold),
+    }
+}
+
+pub fn multiply_matrices_conventional<T, S1, S2>(matrix_1: &ArrayBase<S1, Ix2>, matrix_2: &ArrayBase<S2, Ix2>) -> Array2<T>
+where
+    T: MatrixElement,
+    S1: Data<Elem = T>,
+    S2: Data<Elem = T>,
+{
+    let (row_count, inner_size) = matrix_1.dim();
+    let column_count = matrix_2.ncols();
+    let mut result = Array2::zeros((row_count, column_count));
+
+    for i in 0..row_count {
+        for j in 0..column_count {
+            for k in 0..inner_size {
+                result[[i, j]] += matrix_1[[i, k]] * matrix_2[[k, j]];
+            }
+        }
+    }
+
+    result
+}
+
+pub fn multiply_matrices_strassen<T, S1, S2>(matrix_1: &ArrayBase<S1, Ix2>, matrix_2: &ArrayBase<S2, Ix2>) -> Array2<T>
+where
+    T: MatrixElement,
+    S1: Data<Elem = T>,
+    S2: Data<Elem = T>,
+{
+    multiply_matrices_strassen_threshold(matrix_1, matrix_2, 0)
+}
+
+pub fn multiply_matrices_strassen_threshold<T, S1, S2>(matrix_1: &ArrayBase<S1, Ix2>, matrix_2: &ArrayBase<S2, Ix2>, threshold: usize) -> Array2<T>
+where
+    T: MatrixElement,
+    S1: Data<Elem = T>,
+    S2: Data<Elem = T>,
+{
+    multiply_matrices_strassen_recorded(matrix_1, matrix_2, threshold, &mut NoRecorder)
+}
+
+/// Phase of a recursive Strassen call
+#[derive(Clone, Copy)]
+pub enum StrassenPhase {
+    /// Product of submatrices at or below the threshold, without recursion
+    BaseCase,
+    /// Sums and differences of quadrants, for the operands of M or the quadrants of C
+    Additions,
+    /// Recursive call computing M1 to M7
+    Product(usize),
+    /// Assembly of the quadrants of C
+    Concatenation,
+}
+
+/// Observes the phases of a Strassen multiplication, to trace where its time is spent
+pub trait StrassenRecorder {
+    /// Runs a phase of the recursive call on size x size matrices
+    fn record<R, F: FnOnce(&mut Self) -> R>(&mut self, size: usize, phase: StrassenPhase, run: F) -> R;
+}
+
+/// Recorder of untraced multiplications, which only runs each phase
+pub struct NoRecorder;
+
+impl StrassenRecorder for NoRecorder {
+    #[inline(always)]
+    fn record<R, F: FnOnce(&mut Self) -> R>(&mut self, _size: usize, _phase: StrassenPhase, run: F) -> R {
+        run(self)
+    }
+}
+
+pub fn multiply_matrices_strassen_recorded<T, S1, S2, R>(
+    matrix_1: &ArrayBase<S1, Ix2>,
+    matrix_2: &ArrayBase<S2, Ix2>,
+    threshold: usize,
+    recorder: &mut R,
+) -> Array2<T>
+where
+    T: MatrixElement,
+    S1: Data<Elem = T>,
+    S2: Data<Elem = T>,
+    R: StrassenRecorder,
+{
+    let n = matrix_1.shape()[0];
+
+    if n == 1 {
+        return recorder.record(n, StrassenPhase::BaseCase, |_| arr2(&[[matrix_1[[0, 0]] * matrix_2[[0, 0]]]]));
+    }
+
+    if n <= threshold {
+        return recorder.record(n, StrassenPhase::BaseCase, |_| multiply_matrices_conventional(matrix_1, matrix_2));
+    }
+
+    // Operands of M
+    let operands = recorder.record(n, StrassenPhase::Additions, |_| strassen_operands(matrix_1, matrix_2));
+
+    // M
+    let intermediate_matrices: Vec<Array2<T>> = operands
+        .iter()
+        .enumerate()
+        .map(|(i, (operand_1, operand_2))| {
+            recorder.record(n, StrassenPhase::Product(i + 1), |recorder| {
+                multiply_matrices_strassen_recorded(operand_1, operand_2, threshold, recorder)
+            })
+        })
+        .collect();
+
+    // C
+    let result_quadrants = recorder.record(n, StrassenPhase::Additions, |_| strassen_result_quadrants(&intermediate_matrices));
+
+    recorder.record(n, StrassenPhase::Concatenation, |_| concatenate_quadrants(&result_quadrants))
+}
+
+/// Operands of M1 to M7, where quadrants used as is are borrowed rather than copied
+pub fn strassen_operands<'a, T, S1, S2>(
+    matrix_1: &'a ArrayBase<S1, Ix2>,
+    matrix_2: &'a ArrayBase<S2, Ix2>,
+) -> [(CowArray<'a, T, Ix2>, CowArray<'a, T, Ix2>); 7]
+where
+    T: MatrixElement,
+    S1: Data<Elem = T>,
+    S2: Data<Elem = T>,
+{
+    let n = matrix_1.shape()[0];
+
+    let matrix_1_slices = [
+        matrix_1.slice(s![0..n / 2, 0..n / 2]), // A1,1
+        matrix_1.slice(s![0..n / 2, n / 2..n]), // A1,2
+        matrix_1.slice(s![n / 2..n, 0..n / 2]), // A2,1
+        matrix_1.slice(s![n / 2..n, n / 2..n]), // A2,2
+    ];
+    let matrix_2_slices = [
+        matrix_2.slice(s![0..n / 2, 0..n / 2]), // B1,1
+        matrix_2.slice(s![0..n / 2, n / 2..n]), // B1,2
+        matrix_2.slice(s![n / 2..n, 0..n / 2]), // B2,1
+        matrix_2.slice(s![n / 2..n, n / 2..n]), // B2,2
+    ];
+
+    [
+        ((&matrix_1_slices[0] + &matrix_1_slices[3]).into(), (&matrix_2_slices[0] + &matrix_2_slices[3]).into()), // M1 = (A1,1 + A2,2) * (B1,1 + B2,2)
+        ((&matrix_1_slices[2] + &matrix_1_slices[3]).into(), matrix_2_slices[0].into()),                          // M2 = (A2,1 + A2,2) * B1,1
+        (matrix_1_slices[0].into(), (&matrix_2_slices[1] - &matrix_2_slices[3]).into()),                          // M3 = A1,1 * (B1,2 - B2,2)
+        (matrix_1_slices[3].into(), (&matrix_2_slices[2] - &matrix_2_slices[0]).into()),                          // M4 = A2,2 * (B2,1 - B1,1)
+        ((&matrix_1_slices[0] + &matrix_1_slices[1]).into(), matrix_2_slices[3].into()),                          // M5 = (A1,1 + A1,2) * B2,2
+        ((&matrix_1_slices[2] - &matrix_1_slices[0]).into(), (&matrix_2_slices[0] + &matrix_2_slices[1]).into()), // M6 = (A2,1 - A1,1) * (B1,1 + B1,2)
+        ((&matrix_1_slices[1] - &matrix_1_slices[3]).into(), (&matrix_2_slices[2] + &matrix_2_slices[3]).into()), // M7 = (A1,2 - A2,2) * (B2,1 + B2,2)
+    ]
+}
+
+/// Quadrants of C from M1 to M7
+pub fn strassen_result_quadrants<T: MatrixElement>(intermediate_matrices: &[Array2<T>]) -> [Array2<T>; 4] {
+    [
+        &intermediate_matrices[0] + &intermediate_matrices[3] - &intermediate_matrices[4] + &intermediate_matrices[6], // C1,1 = M1 + M4 - M5 + M7
+        &intermediate_matrices[2] + &intermediate_matrices[4],                                                         // C1,2 = M3 + M5
+        &intermediate_matrices[1] + &intermediate_matrices[3],                                                         // C2,1 = M2 + M4
+        &intermediate_matrices[0] - &intermediate_matrices[1] + &intermediate_matrices[2] + &intermediate_matrices[5], // C2,2 = M1 - M2 + M3 + M6
+    ]
+}
+
+pub fn concatenate_quadrants<T: MatrixElement>(quadrants: &[Array2<T>; 4]) -> Array2<T> {
+    concatenate![
+        Axis(0),
+        concatenate![Axis(1), quadrants[0], quadrants[1]],
+        concatenate![Axis(1), quadrants[2], quadrants[3]]
+    ]
+}
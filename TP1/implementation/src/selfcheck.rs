@@ -0,0 +1,302 @@
+use std::fs;
+use std::process;
+
+use ndarray::{s, Array2};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cli_args::{Algorithm, MatrixFormat, SelfcheckArgs};
+use crate::inversion::{compute_pivot_tolerance, invert_matrix_blockwise, invert_matrix_gauss_jordan};
+use crate::matrix_utils::save_matrix;
+use crate::multiplication_algorithms::{multiply_matrices, multiply_matrices_conventional};
+
+/// Ranges of the random matrix values, from the TP1 instances (0 to 5) to larger signed values
+const VALUE_RANGES: [(i32, i32); 3] = [(0, 5), (-100, 100), (-1000, 1000)];
+
+/// Largest difference allowed between the blockwise and Gauss-Jordan inverses of a diagonally dominant matrix
+const INVERSION_TOLERANCE: f64 = 1e-9;
+
+struct FailingCase {
+    algorithm: Algorithm,
+    threshold: usize,
+    matrix_1: Array2<i32>,
+    matrix_2: Array2<i32>,
+}
+
+impl FailingCase {
+    fn matrix_mut(&mut self, matrix_index: usize) -> &mut Array2<i32> {
+        if matrix_index == 0 { &mut self.matrix_1 } else { &mut self.matrix_2 }
+    }
+}
+
+pub fn run_selfcheck(args: &SelfcheckArgs) {
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let mut case_count = 0usize;
+    let mut mismatch_count = 0usize;
+    let mut failing_cases = Vec::new();
+
+    let mut n = 1;
+    while n <= args.max_size {
+        for &(min_value, max_value) in &VALUE_RANGES {
+            // Skip configurations whose intermediate values could overflow an i32
+            let max_magnitude = i32::max(min_value.abs(), max_value.abs());
+            let configurations: Vec<_> = algorithm_configurations(n)
+                .into_iter()
+                .filter(|&(algorithm, threshold)| max_intermediate_magnitude(algorithm, threshold, n, max_magnitude) <= i32::MAX as i64)
+                .collect();
+
+            for _ in 0..args.case_count {
+                let matrix_1 = Array2::from_shape_simple_fn((n, n), || rng.gen_range(min_value..=max_value));
+                let matrix_2 = Array2::from_shape_simple_fn((n, n), || rng.gen_range(min_value..=max_value));
+
+                for &(algorithm, threshold) in &configurations {
+                    case_count += 1;
+                    if is_result_correct(algorithm, threshold, &matrix_1, &matrix_2) {
+                        continue;
+                    }
+
+                    mismatch_count += 1;
+                    println!("Mismatch: {} with threshold {} on {}x{} matrices with values in [{}, {}]", algorithm, threshold, n, n, min_value, max_value);
+
+                    // Only the first failure of each configuration is shrunk and saved
+                    let is_new_failure = !failing_cases.iter().any(|case: &FailingCase| case.algorithm == algorithm && case.threshold == threshold);
+                    if is_new_failure {
+                        failing_cases.push(shrink_failing_case(FailingCase {
+                            algorithm,
+                            threshold,
+                            matrix_1: matrix_1.clone(),
+                            matrix_2: matrix_2.clone(),
+                        }));
+                    }
+                }
+            }
+        }
+        n *= 2;
+    }
+
+    // Block inversion pads sizes that are not powers of two, so every size is checked
+    for n in 1..=args.max_size {
+        for _ in 0..args.case_count {
+            let matrix = generate_diagonally_dominant_matrix(&mut rng, n);
+
+            for threshold in inversion_thresholds(n) {
+                case_count += 1;
+                if !is_inverse_correct(&matrix, threshold) {
+                    mismatch_count += 1;
+                    println!("Mismatch: block inversion with threshold {} on a {}x{} diagonally dominant matrix", threshold, n, n);
+                }
+            }
+        }
+    }
+
+    println!("Checked {} cases, {} failed", case_count, mismatch_count);
+
+    if failing_cases.is_empty() {
+        // Inversion mismatches are not shrunk, so they have no failing case to save
+        if mismatch_count > 0 {
+            process::exit(1);
+        }
+        return;
+    }
+
+    fs::create_dir_all(&args.failures_directory).expect("Error creating failures directory");
+    for (i, failing_case) in failing_cases.iter().enumerate() {
+        let matrix_1_filename = args.failures_directory.join(format!("failure_{}_1", i));
+        let matrix_2_filename = args.failures_directory.join(format!("failure_{}_2", i));
+        save_matrix(&failing_case.matrix_1, &matrix_1_filename, Some(MatrixFormat::Text)).expect("Error saving failing case");
+        save_matrix(&failing_case.matrix_2, &matrix_2_filename, Some(MatrixFormat::Text)).expect("Error saving failing case");
+
+        println!(
+            "Minimal failing case {}: ./tp -a {} --threshold {} --e1 {} --e2 {} -p",
+            i,
+            failing_case.algorithm,
+            failing_case.threshold,
+            matrix_1_filename.display(),
+            matrix_2_filename.display()
+        );
+    }
+
+    process::exit(1);
+}
+
+/// Every algorithm with every power of two threshold up to the matrix size, plus a threshold of 0
+fn algorithm_configurations(n: usize) -> Vec<(Algorithm, usize)> {
+    let mut configurations = Vec::new();
+
+    for &algorithm in &Algorithm::ALL {
+        configurations.push((algorithm, 0));
+
+        if algorithm == Algorithm::StrassenThreshold {
+            let mut threshold = 1;
+            while threshold <= n {
+                configurations.push((algorithm, threshold));
+                threshold *= 2;
+            }
+        }
+    }
+
+    configurations
+}
+
+/// Bound on the values computed by an algorithm on n x n matrices whose values have a magnitude of at most
+/// max_magnitude. Each Strassen recursion level adds two quadrants to get the operands of its sub-products, so at depth
+/// d the operands reach 2^d * max_magnitude, and the sub-products of size s reach s * (2^d * max_magnitude)². Adding up
+/// to three sub-products for a quadrant of C then gives 3 * n * (n / leaf size) * max_magnitude² at most.
+fn max_intermediate_magnitude(algorithm: Algorithm, threshold: usize, n: usize, max_magnitude: i32) -> i64 {
+    // Size of the products computed without recursion
+    let leaf_size = match algorithm.strassen_threshold(threshold) {
+        Some(threshold) if threshold < n => {
+            let mut leaf_size = n;
+            while leaf_size > threshold.max(1) {
+                leaf_size /= 2;
+            }
+            leaf_size
+        }
+        _ => n,
+    };
+
+    let max_magnitude = max_magnitude as i64;
+    3 * n as i64 * (n / leaf_size) as i64 * max_magnitude * max_magnitude
+}
+
+/// Every power of two threshold below the matrix size, so that the block recursion is used
+fn inversion_thresholds(n: usize) -> Vec<usize> {
+    let mut thresholds = vec![1];
+    while thresholds.last().unwrap() * 2 < n {
+        thresholds.push(thresholds.last().unwrap() * 2);
+    }
+    thresholds
+}
+
+/// Random matrix with values in [-1, 1] and a diagonal large enough that every leading block and Schur complement
+/// is invertible
+fn generate_diagonally_dominant_matrix(rng: &mut StdRng, n: usize) -> Array2<f64> {
+    let mut matrix = Array2::from_shape_simple_fn((n, n), || rng.gen_range(-1.0..=1.0));
+    for i in 0..n {
+        matrix[[i, i]] += n as f64;
+    }
+    matrix
+}
+
+fn is_inverse_correct(matrix: &Array2<f64>, threshold: usize) -> bool {
+    let tolerance = compute_pivot_tolerance(matrix);
+    match (invert_matrix_blockwise(matrix, threshold, tolerance), invert_matrix_gauss_jordan(matrix, tolerance)) {
+        (Some(inverse), Some(expected_inverse)) => {
+            inverse.iter().zip(expected_inverse.iter()).all(|(x, y)| (x - y).abs() <= INVERSION_TOLERANCE)
+        }
+        _ => false,
+    }
+}
+
+fn is_result_correct(algorithm: Algorithm, threshold: usize, matrix_1: &Array2<i32>, matrix_2: &Array2<i32>) -> bool {
+    multiply_matrices(algorithm, matrix_1, matrix_2, threshold) == multiply_matrices_conventional(matrix_1, matrix_2)
+}
+
+/// Greedily shrinks a failing case: first its size by keeping quadrants, then its values towards 0
+fn shrink_failing_case(mut failing_case: FailingCase) -> FailingCase {
+    let fails = |case: &FailingCase| !is_result_correct(case.algorithm, case.threshold, &case.matrix_1, &case.matrix_2);
+
+    // Shrink size, trying every pair of quadrants of the two matrices
+    'size_loop: while failing_case.matrix_1.nrows() > 1 {
+        let half = failing_case.matrix_1.nrows() / 2;
+        let quadrants = |matrix: &Array2<i32>| {
+            vec![
+                matrix.slice(s![..half, ..half]).to_owned(),
+                matrix.slice(s![..half, half..]).to_owned(),
+                matrix.slice(s![half.., ..half]).to_owned(),
+                matrix.slice(s![half.., half..]).to_owned(),
+            ]
+        };
+
+        for matrix_1 in quadrants(&failing_case.matrix_1) {
+            for matrix_2 in quadrants(&failing_case.matrix_2) {
+                let candidate = FailingCase {
+                    algorithm: failing_case.algorithm,
+                    threshold: failing_case.threshold,
+                    matrix_1: matrix_1.clone(),
+                    matrix_2,
+                };
+                if fails(&candidate) {
+                    failing_case = candidate;
+                    continue 'size_loop;
+                }
+            }
+        }
+
+        break;
+    }
+
+    // Shrink values, setting each one to 0 or halving it while the case keeps failing
+    let mut has_shrunk = true;
+    while has_shrunk {
+        has_shrunk = false;
+
+        for matrix_index in 0..2 {
+            let n = failing_case.matrix_1.nrows();
+            for position in (0..n * n).map(|index| [index / n, index % n]) {
+                let value = failing_case.matrix_mut(matrix_index)[position];
+                for &candidate in &[0, value / 2] {
+                    if candidate == value {
+                        continue;
+                    }
+
+                    failing_case.matrix_mut(matrix_index)[position] = candidate;
+                    if fails(&failing_case) {
+                        has_shrunk = true;
+                        break;
+                    }
+                    failing_case.matrix_mut(matrix_index)[position] = value;
+                }
+            }
+        }
+    }
+
+    failing_case
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Matrix whose values alternate between the two bounds of a range, so that Strassen's sums of quadrants and of
+    /// sub-products reach large magnitudes
+    fn checkerboard_matrix(n: usize, min_value: i32, max_value: i32) -> Array2<i32> {
+        Array2::from_shape_fn((n, n), |(i, j)| if (i / 2 + j) % 2 == 0 { max_value } else { min_value })
+    }
+
+    #[test]
+    fn bound_skips_full_recursion_on_large_values() {
+        // The largest value of a leaf product alone is about 64² * 1000², which does not fit in an i32
+        assert!(max_intermediate_magnitude(Algorithm::Strassen, 0, 64, 1000) > i32::MAX as i64);
+        assert!(max_intermediate_magnitude(Algorithm::Conventional, 0, 64, 1000) <= i32::MAX as i64);
+    }
+
+    #[test]
+    fn bound_is_larger_with_deeper_recursion() {
+        let bounds: Vec<_> = [64, 16, 4, 1]
+            .iter()
+            .map(|&threshold| max_intermediate_magnitude(Algorithm::StrassenThreshold, threshold, 64, 100))
+            .collect();
+        assert!(bounds.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn configurations_within_bound_do_not_overflow() {
+        // Arithmetic overflow panics in test builds, so any configuration whose bound is wrong would fail here
+        let mut n = 1;
+        while n <= 64 {
+            for &(min_value, max_value) in &VALUE_RANGES {
+                let max_magnitude = i32::max(min_value.abs(), max_value.abs());
+                let matrix_1 = checkerboard_matrix(n, min_value, max_value);
+                let matrix_2 = checkerboard_matrix(n, max_value, min_value);
+
+                for (algorithm, threshold) in algorithm_configurations(n) {
+                    if max_intermediate_magnitude(algorithm, threshold, n, max_magnitude) <= i32::MAX as i64 {
+                        assert!(is_result_correct(algorithm, threshold, &matrix_1, &matrix_2));
+                    }
+                }
+            }
+            n *= 2;
+        }
+    }
+}
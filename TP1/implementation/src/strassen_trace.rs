@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ndarray::{Array2, ArrayBase, Data, Ix2};
+use serde::Serialize;
+
+use crate::matrix_element::MatrixElement;
+use crate::multiplication_algorithms::{multiply_matrices_strassen_recorded, StrassenPhase, StrassenRecorder};
+
+/// Recursive call of a traced Strassen multiplication
+#[derive(Serialize)]
+struct TraceCall {
+    id: usize,
+    parent: Option<usize>,
+    depth: usize,
+    size: usize,
+    /// M product computed by the call, or C for the whole multiplication
+    product: String,
+    total_ms: f64,
+    additions_ms: f64,
+    sub_products_ms: f64,
+    concatenation_ms: f64,
+    base_case_ms: f64,
+}
+
+/// Complete event of the Chrome trace-event format
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    /// Start time in µs
+    ts: f64,
+    /// Duration in µs
+    dur: f64,
+    pid: u32,
+    tid: u32,
+    args: TraceEventArgs,
+}
+
+#[derive(Serialize)]
+struct TraceEventArgs {
+    depth: usize,
+    size: usize,
+}
+
+/// Trace file, which viewers load through traceEvents while ignoring the calls
+#[derive(Serialize)]
+struct TraceFile<'a> {
+    #[serde(rename = "traceEvents")]
+    trace_events: &'a [TraceEvent],
+    #[serde(rename = "displayTimeUnit")]
+    display_time_unit: &'static str,
+    calls: &'a [TraceCall],
+}
+
+pub struct StrassenTracer {
+    start: Instant,
+    calls: Vec<TraceCall>,
+    events: Vec<TraceEvent>,
+    /// Indices of the calls being executed, the innermost last
+    call_stack: Vec<usize>,
+}
+
+impl StrassenTracer {
+    /// Multiplies two matrices with Strassen, recording every recursive call
+    pub fn trace<T, S1, S2>(matrix_1: &ArrayBase<S1, Ix2>, matrix_2: &ArrayBase<S2, Ix2>, threshold: usize) -> (Array2<T>, StrassenTracer)
+    where
+        T: MatrixElement,
+        S1: Data<Elem = T>,
+        S2: Data<Elem = T>,
+    {
+        let mut tracer = StrassenTracer {
+            start: Instant::now(),
+            calls: Vec::new(),
+            events: Vec::new(),
+            call_stack: Vec::new(),
+        };
+
+        let n = matrix_1.nrows();
+        let result = tracer.record_call(n, "C".to_string(), |tracer| multiply_matrices_strassen_recorded(matrix_1, matrix_2, threshold, tracer));
+
+        (result, tracer)
+    }
+
+    /// Writes the recursive calls along with their events in Chrome trace-event format
+    pub fn save(&self, filename: &Path) -> Result<(), Box<dyn Error>> {
+        let trace_file = TraceFile {
+            trace_events: &self.events,
+            display_time_unit: "ms",
+            calls: &self.calls,
+        };
+
+        let writer = BufWriter::new(File::create(filename)?);
+        serde_json::to_writer_pretty(writer, &trace_file)?;
+        Ok(())
+    }
+
+    fn record_call<R, F: FnOnce(&mut Self) -> R>(&mut self, size: usize, product: String, run: F) -> R {
+        let id = self.calls.len();
+        self.calls.push(TraceCall {
+            id,
+            parent: self.call_stack.last().copied(),
+            depth: self.call_stack.len(),
+            size,
+            product: product.clone(),
+            total_ms: 0.0,
+            additions_ms: 0.0,
+            sub_products_ms: 0.0,
+            concatenation_ms: 0.0,
+            base_case_ms: 0.0,
+        });
+        self.call_stack.push(id);
+
+        let start = Instant::now();
+        let result = run(self);
+        let duration = start.elapsed();
+
+        self.call_stack.pop();
+        self.calls[id].total_ms = duration.as_secs_f64() * 1000.0;
+        self.push_event(product, "product", start, duration, id);
+
+        result
+    }
+
+    fn push_event(&mut self, name: String, category: &'static str, start: Instant, duration: Duration, call_id: usize) {
+        self.events.push(TraceEvent {
+            name,
+            cat: category,
+            ph: "X",
+            ts: start.duration_since(self.start).as_secs_f64() * 1e6,
+            dur: duration.as_secs_f64() * 1e6,
+            pid: 1,
+            tid: 1,
+            args: TraceEventArgs {
+                depth: self.calls[call_id].depth,
+                size: self.calls[call_id].size,
+            },
+        });
+    }
+}
+
+impl StrassenRecorder for StrassenTracer {
+    fn record<R, F: FnOnce(&mut Self) -> R>(&mut self, size: usize, phase: StrassenPhase, run: F) -> R {
+        let call_id = *self.call_stack.last().unwrap();
+
+        if let StrassenPhase::Product(index) = phase {
+            let child_id = self.calls.len();
+            let result = self.record_call(size / 2, format!("M{}", index), run);
+            self.calls[call_id].sub_products_ms += self.calls[child_id].total_ms;
+            return result;
+        }
+
+        let start = Instant::now();
+        let result = run(self);
+        let duration = start.elapsed();
+
+        let elapsed_ms = duration.as_secs_f64() * 1000.0;
+        let call = &mut self.calls[call_id];
+        let name = match phase {
+            StrassenPhase::BaseCase => {
+                call.base_case_ms += elapsed_ms;
+                "base case"
+            }
+            StrassenPhase::Additions => {
+                call.additions_ms += elapsed_ms;
+                "additions"
+            }
+            StrassenPhase::Concatenation => {
+                call.concatenation_ms += elapsed_ms;
+                "concatenation"
+            }
+            StrassenPhase::Product(_) => unreachable!(),
+        };
+        self.push_event(name.to_string(), "phase", start, duration, call_id);
+
+        result
+    }
+}
@@ -18,11 +18,13 @@ ALGORITHMS = {
     'Greedy': 'glouton',
     'BranchAndBound': 'branch_bound',
     'Tabu': 'tabou',
+    'SimulatedAnnealing': 'recuit',
 }
 MAX_GRAPH_SIZES = {
     'Greedy': None,
     'BranchAndBound': 72,
     'Tabu': None,
+    'SimulatedAnnealing': None,
 }
 THEORETICAL_COMPLEXITY_FUNCTIONS = {
     'Greedy': {
@@ -37,6 +39,10 @@ THEORETICAL_COMPLEXITY_FUNCTIONS = {
         'function': lambda x: np.power(x, 3),
         'string': '{}^3',
     },
+    'SimulatedAnnealing': { # O(n) moves per temperature, each with a Kempe chain of O(n^2) edges
+        'function': lambda x: np.power(x, 3),
+        'string': '{}^3',
+    },
 }
 
 
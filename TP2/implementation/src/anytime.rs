@@ -0,0 +1,85 @@
+use std::io::{self, Write};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::coloring::Coloring;
+use crate::graph_utils::format_coloring;
+
+/// Set by the SIGINT handler, to stop the solver and return its best solution
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Stopping condition of a solver that improves its solution over time, which also prints each improving coloring
+pub struct Anytime {
+    start: Instant,
+    deadline: Option<Instant>,
+    is_streaming: bool,
+    show_result: bool,
+    best_color_count: Option<usize>,
+}
+
+impl Anytime {
+    /// Never stops the solver and prints nothing
+    pub fn unlimited() -> Self {
+        Anytime {
+            start: Instant::now(),
+            deadline: None,
+            is_streaming: false,
+            show_result: false,
+            best_color_count: None,
+        }
+    }
+
+    /// Stops the solver after the time limit or on SIGINT, and prints each improving coloring with its elapsed time,
+    /// along with the coloring itself if show_result is set
+    pub fn with_time_limit(time_limit: Duration, show_result: bool) -> Self {
+        ctrlc::set_handler(|| {
+            // A second SIGINT kills the process, in case the solver does not stop
+            if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                process::exit(130);
+            }
+        })
+        .expect("Error setting SIGINT handler");
+
+        let start = Instant::now();
+        Anytime {
+            start,
+            // A time limit too far in the future to be represented never stops the solver
+            deadline: start.checked_add(time_limit),
+            is_streaming: true,
+            show_result,
+            best_color_count: None,
+        }
+    }
+
+    pub fn should_stop(&self) -> bool {
+        INTERRUPTED.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Prints the coloring if it uses strictly fewer colors than the previous ones
+    pub fn report(&mut self, node_colors: &Coloring) {
+        let color_count = node_colors.color_count();
+        if self
+            .best_color_count
+            .is_some_and(|best_color_count| color_count >= best_color_count)
+        {
+            return;
+        }
+        self.best_color_count = Some(color_count);
+
+        if !self.is_streaming {
+            return;
+        }
+
+        // Flush right away, so that a program reading the output sees each coloring as soon as it is found
+        let elapsed_ms = self.start.elapsed().as_secs_f64() * 1000.0;
+        println!("{} colors after {} ms", color_count, elapsed_ms);
+        if self.show_result {
+            println!("{}", format_coloring(node_colors));
+        }
+        io::stdout().flush().expect("Error printing coloring");
+    }
+}
@@ -1,64 +1,95 @@
 use std::collections::HashMap;
 
-use petgraph::matrix_graph::{NodeIndex, UnMatrix};
-
+use crate::anytime::Anytime;
+use crate::clique::find_lower_bound_clique;
+use crate::coloring::Coloring;
+use crate::graph::{Graph, NodeIndex};
 use crate::graph_utils::{
-    count_colors, find_node_with_maximum_degree, get_neighbor_unique_colors, get_node_degrees,
+    find_node_with_maximum_degree, get_neighbor_unique_colors, get_node_degrees,
 };
 use crate::greedy_algorithm::{find_node_with_greedy_choice, solve_with_greedy};
 
-pub fn solve_with_branch_and_bound(graph: &UnMatrix<(), ()>) -> HashMap<NodeIndex, usize> {
+/// Returns an optimal coloring, along with the lower bound proven on the number of colors, which equals the number of
+/// colors of the coloring once the search is complete. If the search is stopped before, returns the best coloring found
+/// with the clique lower bound.
+pub fn solve_with_branch_and_bound<G: Graph>(
+    graph: &G,
+    anytime: &mut Anytime,
+) -> (Coloring, usize) {
     let node_degrees = get_node_degrees(graph);
 
     // Get initial best solution and upper bound using greedy algorithm
     let mut best_node_colors = solve_with_greedy(graph);
-    let mut best_color_count = count_colors(&best_node_colors);
+    let mut best_color_count = best_node_colors.color_count();
+    anytime.report(&best_node_colors);
+
+    // Get lower bound from the size of a clique, whose nodes all need different colors
+    let lower_bound = find_lower_bound_clique(graph).len();
+
+    // Stop if the greedy coloring is already optimal
+    if best_color_count <= lower_bound {
+        return (best_node_colors, best_color_count);
+    }
 
     // Stack of node color combinations to visit
     let mut color_combinations_to_visit = Vec::new();
 
     // Create new incomplete node color combination with starting node
     let starting_node_index = find_node_with_maximum_degree(&node_degrees);
-    let starting_node_color = HashMap::from([(starting_node_index, 0)]);
+    let mut starting_node_color = Coloring::new(graph.node_count());
+    starting_node_color.set(starting_node_index, 0);
     color_combinations_to_visit.push(starting_node_color);
 
     // Visit node color combinations using branch and bound
     while let Some(current_node_colors) = color_combinations_to_visit.pop() {
-        let is_coloring_complete = current_node_colors.len() == graph.node_count();
-        let current_color_count = count_colors(&current_node_colors);
+        if anytime.should_stop() {
+            return (best_node_colors, lower_bound);
+        }
+
+        let is_coloring_complete = current_node_colors.is_complete();
+        let current_color_count = current_node_colors.color_count();
 
         if is_coloring_complete && current_color_count < best_color_count {
             best_node_colors = current_node_colors;
             best_color_count = current_color_count;
+            anytime.report(&best_node_colors);
+
+            // Stop as soon as the upper bound meets the lower bound
+            if best_color_count == lower_bound {
+                break;
+            }
         } else if current_color_count < best_color_count {
             let new_color_combinations =
-                extend_node_colors(graph, &node_degrees, &current_node_colors);
+                extend_node_colors(graph, &node_degrees, &current_node_colors, best_color_count);
             for node_colors in new_color_combinations {
                 color_combinations_to_visit.push(node_colors)
             }
         }
     }
 
-    best_node_colors
+    // The search is complete, so no coloring uses fewer colors than the best one
+    (best_node_colors, best_color_count)
 }
 
-fn extend_node_colors(
-    graph: &UnMatrix<(), ()>,
+fn extend_node_colors<G: Graph>(
+    graph: &G,
     node_degrees: &HashMap<NodeIndex, usize>,
-    node_colors: &HashMap<NodeIndex, usize>,
-) -> Vec<HashMap<NodeIndex, usize>> {
+    node_colors: &Coloring,
+    best_color_count: usize,
+) -> Vec<Coloring> {
     let mut color_combinations = Vec::new();
 
     // Get next uncolored node to color
     let uncolored_node_index = find_node_with_greedy_choice(graph, node_degrees, node_colors);
 
-    // Generate new partial color combinations from next uncolored node
+    // Generate new partial color combinations from next uncolored node, only with colors that keep the color count
+    // below the best one, so that a node whose neighbors use all these colors prunes the branch
     let neighbor_colors = get_neighbor_unique_colors(graph, uncolored_node_index, node_colors);
-    let color_count = count_colors(node_colors);
-    for i in 0..color_count + 1 {
+    let color_count = node_colors.color_count();
+    for i in 0..(color_count + 1).min(best_color_count - 1) {
         if !neighbor_colors.contains(&i) {
             let mut new_node_colors = node_colors.clone();
-            new_node_colors.insert(uncolored_node_index, i);
+            new_node_colors.set(uncolored_node_index, i);
             color_combinations.push(new_node_colors);
         }
     }
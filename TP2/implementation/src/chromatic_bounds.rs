@@ -0,0 +1,278 @@
+use crate::clique::{find_lower_bound_clique, find_maximum_clique, EXACT_CLIQUE_MAX_NODE_COUNT};
+use crate::graph::{Graph, NodeIndex, SparseGraph};
+use crate::greedy_algorithm::{find_degeneracy_order, solve_with_greedy};
+
+/// Lower and upper bounds on the chromatic number of a graph
+pub struct ChromaticBounds {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// Clique whose size is a lower bound, maximum on small graphs
+    pub clique: Vec<NodeIndex>,
+    pub is_clique_maximum: bool,
+    /// Upper bound on the size of an independent set, exact on small graphs
+    pub independence_bound: usize,
+    pub is_independence_number_exact: bool,
+    /// Node count divided by the independence bound, rounded up, which is at most the fractional chromatic number
+    pub fractional_clique_bound: usize,
+    /// Maximum degree plus one
+    pub max_degree_bound: usize,
+    /// Maximum degree, or one more for a component that is a complete graph or an odd cycle
+    pub brooks_bound: usize,
+    /// Degeneracy plus one, which smallest-last coloring reaches
+    pub degeneracy_bound: usize,
+    /// Colors used by DSATUR
+    pub greedy_bound: usize,
+}
+
+impl ChromaticBounds {
+    pub fn lower_bound(&self) -> usize {
+        self.clique.len().max(self.fractional_clique_bound)
+    }
+
+    pub fn upper_bound(&self) -> usize {
+        [
+            self.max_degree_bound,
+            self.brooks_bound,
+            self.degeneracy_bound,
+            self.greedy_bound,
+        ]
+        .iter()
+        .copied()
+        .min()
+        .unwrap()
+    }
+
+    /// Human-readable report, with nodes numbered from 1 as in DIMACS files
+    pub fn format_text(&self) -> String {
+        let clique_kind = if self.is_clique_maximum {
+            "maximum"
+        } else {
+            "heuristic"
+        };
+        let independence_kind = if self.is_independence_number_exact {
+            "independence number"
+        } else {
+            "independence number at most"
+        };
+        let chromatic_number = if self.lower_bound() == self.upper_bound() {
+            format!("Chromatic number: {}", self.lower_bound())
+        } else {
+            format!(
+                "Chromatic number: between {} and {}",
+                self.lower_bound(),
+                self.upper_bound()
+            )
+        };
+
+        [
+            format!("Nodes: {}, edges: {}", self.node_count, self.edge_count),
+            "Lower bounds".to_string(),
+            format!(
+                "  Clique: {} ({}), nodes {}",
+                self.clique.len(),
+                clique_kind,
+                format_node_list(&self.clique, " ")
+            ),
+            format!(
+                "  Fractional clique: {} ({} {})",
+                self.fractional_clique_bound, independence_kind, self.independence_bound
+            ),
+            "Upper bounds".to_string(),
+            format!("  Maximum degree + 1: {}", self.max_degree_bound),
+            format!("  Brooks: {}", self.brooks_bound),
+            format!("  Degeneracy + 1: {}", self.degeneracy_bound),
+            format!("  DSATUR: {}", self.greedy_bound),
+            chromatic_number,
+        ]
+        .join("\n")
+    }
+
+    /// JSON report, with nodes numbered from 1 as in DIMACS files
+    pub fn format_json(&self) -> String {
+        [
+            "{".to_string(),
+            format!("  \"nodes\": {},", self.node_count),
+            format!("  \"edges\": {},", self.edge_count),
+            "  \"lower_bounds\": {".to_string(),
+            format!(
+                "    \"clique\": {{\"size\": {}, \"maximum\": {}, \"nodes\": [{}]}},",
+                self.clique.len(),
+                self.is_clique_maximum,
+                format_node_list(&self.clique, ", ")
+            ),
+            format!(
+                "    \"fractional_clique\": {{\"bound\": {}, \"independence_bound\": {}, \
+                 \"exact_independence_number\": {}}}",
+                self.fractional_clique_bound,
+                self.independence_bound,
+                self.is_independence_number_exact
+            ),
+            "  },".to_string(),
+            "  \"upper_bounds\": {".to_string(),
+            format!("    \"max_degree\": {},", self.max_degree_bound),
+            format!("    \"brooks\": {},", self.brooks_bound),
+            format!("    \"degeneracy\": {},", self.degeneracy_bound),
+            format!("    \"dsatur\": {}", self.greedy_bound),
+            "  },".to_string(),
+            format!("  \"lower_bound\": {},", self.lower_bound()),
+            format!("  \"upper_bound\": {}", self.upper_bound()),
+            "}".to_string(),
+        ]
+        .join("\n")
+    }
+}
+
+fn format_node_list(node_indexes: &[NodeIndex], separator: &str) -> String {
+    node_indexes
+        .iter()
+        .map(|node_index| (node_index.index() + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+pub fn compute_chromatic_bounds<G: Graph>(graph: &G) -> ChromaticBounds {
+    let (independence_bound, is_independence_number_exact) = find_independence_bound(graph);
+
+    let mut clique = find_lower_bound_clique(graph);
+    clique.sort();
+
+    ChromaticBounds {
+        node_count: graph.node_count(),
+        edge_count: graph.edge_count(),
+        clique,
+        is_clique_maximum: graph.node_count() <= EXACT_CLIQUE_MAX_NODE_COUNT,
+        independence_bound,
+        is_independence_number_exact,
+        fractional_clique_bound: graph.node_count().div_ceil(independence_bound.max(1)),
+        max_degree_bound: graph
+            .node_indices()
+            .map(|node_index| graph.degree(node_index) + 1)
+            .max()
+            .unwrap_or(0),
+        brooks_bound: find_brooks_bound(graph),
+        degeneracy_bound: find_degeneracy(graph).map_or(0, |degeneracy| degeneracy + 1),
+        greedy_bound: solve_with_greedy(graph).color_count(),
+    }
+}
+
+/// Independence number, as the maximum clique of the complement on small graphs, or otherwise the size of a greedy
+/// clique cover, as an independent set has at most one node in each clique. Returns whether it is exact.
+fn find_independence_bound<G: Graph>(graph: &G) -> (usize, bool) {
+    if graph.node_count() <= EXACT_CLIQUE_MAX_NODE_COUNT {
+        let complement_edges: Vec<_> = graph
+            .node_indices()
+            .flat_map(|source_node_index| {
+                graph
+                    .node_indices()
+                    .filter(move |&target_node_index| {
+                        source_node_index < target_node_index
+                            && !graph.has_edge(source_node_index, target_node_index)
+                    })
+                    .map(move |target_node_index| {
+                        (source_node_index.index(), target_node_index.index())
+                    })
+            })
+            .collect();
+        let complement = SparseGraph::from_edges(graph.node_count(), &complement_edges);
+        return (find_maximum_clique(&complement).len(), true);
+    }
+
+    (find_greedy_clique_cover_size(graph), false)
+}
+
+/// Number of cliques covering the nodes, when repeatedly growing a clique from the first uncovered node by adding the
+/// uncovered candidate of maximum degree, as in find_greedy_clique
+fn find_greedy_clique_cover_size<G: Graph>(graph: &G) -> usize {
+    let mut is_covered = vec![false; graph.node_count()];
+    let mut clique_count = 0usize;
+
+    for starting_node_index in graph.node_indices() {
+        if is_covered[starting_node_index.index()] {
+            continue;
+        }
+
+        is_covered[starting_node_index.index()] = true;
+        clique_count += 1;
+
+        let mut candidates: Vec<_> = graph
+            .neighbors(starting_node_index)
+            .filter(|node_index| !is_covered[node_index.index()])
+            .collect();
+        while let Some(&node_index) = candidates
+            .iter()
+            .max_by_key(|&&node_index| graph.degree(node_index))
+        {
+            is_covered[node_index.index()] = true;
+            candidates.retain(|&candidate| {
+                candidate != node_index && graph.has_edge(node_index, candidate)
+            });
+        }
+    }
+
+    clique_count
+}
+
+/// Brooks' theorem: a connected graph needs at most as many colors as its maximum degree, unless it is a complete graph
+/// or an odd cycle, which need one more. The bound is the largest one among the connected components.
+fn find_brooks_bound<G: Graph>(graph: &G) -> usize {
+    let mut is_visited = vec![false; graph.node_count()];
+    let mut brooks_bound = 0usize;
+
+    for starting_node_index in graph.node_indices() {
+        if is_visited[starting_node_index.index()] {
+            continue;
+        }
+
+        // Breadth-first search of the component
+        is_visited[starting_node_index.index()] = true;
+        let mut component = vec![starting_node_index];
+        let mut i = 0;
+        while i < component.len() {
+            for neighbor_node_index in graph.neighbors(component[i]) {
+                if !is_visited[neighbor_node_index.index()] {
+                    is_visited[neighbor_node_index.index()] = true;
+                    component.push(neighbor_node_index);
+                }
+            }
+            i += 1;
+        }
+
+        let degrees: Vec<_> = component
+            .iter()
+            .map(|&node_index| graph.degree(node_index))
+            .collect();
+        let max_degree = degrees.iter().copied().max().unwrap();
+        let is_complete = degrees.iter().all(|&degree| degree == component.len() - 1);
+        let is_odd_cycle = component.len() % 2 == 1 && degrees.iter().all(|&degree| degree == 2);
+
+        let component_bound = if is_complete || is_odd_cycle {
+            max_degree + 1
+        } else {
+            max_degree
+        };
+        brooks_bound = brooks_bound.max(component_bound);
+    }
+
+    brooks_bound
+}
+
+/// Largest number of neighbors of a node removed after it in the degeneracy order, or None for an empty graph
+fn find_degeneracy<G: Graph>(graph: &G) -> Option<usize> {
+    let mut removal_positions = vec![0usize; graph.node_count()];
+    for (position, node_index) in find_degeneracy_order(graph).into_iter().enumerate() {
+        removal_positions[node_index.index()] = position;
+    }
+
+    graph
+        .node_indices()
+        .map(|node_index| {
+            graph
+                .neighbors(node_index)
+                .filter(|neighbor_node_index| {
+                    removal_positions[neighbor_node_index.index()]
+                        > removal_positions[node_index.index()]
+                })
+                .count()
+        })
+        .max()
+}
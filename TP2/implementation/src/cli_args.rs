@@ -1,12 +1,39 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
+use structopt::clap::{Error, ErrorKind};
 use structopt::StructOpt;
 
-#[derive(StructOpt)]
+use crate::evolutionary_algorithm::EvolutionaryConfig;
+use crate::simulated_annealing_algorithm::AnnealingConfig;
+use crate::tabu_search_algorithm::TabuConfig;
+
+#[derive(StructOpt, Clone, Copy, Debug)]
 pub enum Algorithm {
+    /// DSATUR
     Greedy,
     BranchAndBound,
     Tabu,
+    /// Hybrid evolutionary algorithm
+    Evolutionary,
+    SimulatedAnnealing,
+    RecursiveLargestFirst,
+    WelshPowell,
+    SmallestLast,
+    RandomGreedy,
+}
+
+impl Algorithm {
+    /// Whether the algorithm colors each node once without backtracking, and can give the initial tabu search solution
+    pub fn is_constructive(self) -> bool {
+        !matches!(
+            self,
+            Algorithm::BranchAndBound
+                | Algorithm::Tabu
+                | Algorithm::Evolutionary
+                | Algorithm::SimulatedAnnealing
+        )
+    }
 }
 
 impl FromStr for Algorithm {
@@ -16,20 +43,129 @@ impl FromStr for Algorithm {
             "glouton" => Ok(Algorithm::Greedy),
             "branch_bound" => Ok(Algorithm::BranchAndBound),
             "tabou" => Ok(Algorithm::Tabu),
+            "hea" => Ok(Algorithm::Evolutionary),
+            "recuit" => Ok(Algorithm::SimulatedAnnealing),
+            "rlf" => Ok(Algorithm::RecursiveLargestFirst),
+            "welsh_powell" => Ok(Algorithm::WelshPowell),
+            "smallest_last" => Ok(Algorithm::SmallestLast),
+            "random_greedy" => Ok(Algorithm::RandomGreedy),
             _ => Err(format!("Could not parse algorithm: {}", algorithm)),
         }
     }
 }
 
+/// How simulated annealing lowers its temperature after each batch of moves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoolingSchedule {
+    /// Multiplies the temperature by the cooling rate
+    Geometric,
+    /// Lowers the temperature more when the objective varied less during the batch
+    Adaptive,
+}
+
+impl FromStr for CoolingSchedule {
+    type Err = String;
+    fn from_str(cooling_schedule: &str) -> Result<Self, Self::Err> {
+        match cooling_schedule {
+            "geometric" => Ok(CoolingSchedule::Geometric),
+            "adaptive" => Ok(CoolingSchedule::Adaptive),
+            _ => Err(format!(
+                "Could not parse cooling schedule: {}",
+                cooling_schedule
+            )),
+        }
+    }
+}
+
+/// Format of a graph file
+#[derive(Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    /// Detected from the extension, then from the content
+    Auto,
+    Dimacs,
+    Matrix,
+    EdgeList,
+    DimacsBinary,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "auto" => Ok(GraphFormat::Auto),
+            "dimacs" => Ok(GraphFormat::Dimacs),
+            "matrix" => Ok(GraphFormat::Matrix),
+            "edgelist" => Ok(GraphFormat::EdgeList),
+            "binary" => Ok(GraphFormat::DimacsBinary),
+            _ => Err(format!("Could not parse graph format: {}", format)),
+        }
+    }
+}
+
+/// Format of an exported k-colorability model
+#[derive(Clone, Copy, PartialEq)]
+pub enum ModelFormat {
+    /// DIMACS CNF formula for SAT solvers
+    Cnf,
+    /// Assignment integer linear program in the CPLEX LP format
+    Lp,
+    /// Assignment integer linear program in the free MPS format
+    Mps,
+}
+
+impl FromStr for ModelFormat {
+    type Err = String;
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "cnf" => Ok(ModelFormat::Cnf),
+            "lp" => Ok(ModelFormat::Lp),
+            "mps" => Ok(ModelFormat::Mps),
+            _ => Err(format!("Could not parse model format: {}", format)),
+        }
+    }
+}
+
+/// Representation of a loaded graph
+#[derive(Clone, Copy, PartialEq)]
+pub enum GraphRepresentation {
+    /// Chosen from the graph size and density
+    Auto,
+    Dense,
+    Sparse,
+}
+
+impl FromStr for GraphRepresentation {
+    type Err = String;
+    fn from_str(representation: &str) -> Result<Self, Self::Err> {
+        match representation {
+            "auto" => Ok(GraphRepresentation::Auto),
+            "dense" => Ok(GraphRepresentation::Dense),
+            "sparse" => Ok(GraphRepresentation::Sparse),
+            _ => Err(format!(
+                "Could not parse graph representation: {}",
+                representation
+            )),
+        }
+    }
+}
+
 #[derive(StructOpt)]
 pub struct Cli {
     /// The multiplication algorithm to use
     #[structopt(short)]
-    pub algorithm: Algorithm,
+    pub algorithm: Option<Algorithm>,
 
-    /// Path to the graph adjacency matrix file
+    /// Path to the graph file
     #[structopt(short = "e", parse(from_os_str))]
-    pub filename: PathBuf,
+    pub filename: Option<PathBuf>,
+
+    /// Format of the graph file (auto, dimacs, matrix, edgelist or binary)
+    #[structopt(long = "format", default_value = "auto")]
+    pub format: GraphFormat,
+
+    /// Graph representation (auto, dense or sparse), chosen from the graph density by default
+    #[structopt(long = "representation", default_value = "auto")]
+    pub representation: GraphRepresentation,
 
     /// Prints the solution
     #[structopt(short = "p")]
@@ -38,4 +174,279 @@ pub struct Cli {
     /// Prints execution time in ns
     #[structopt(short = "t")]
     pub show_exec_time: bool,
+
+    /// Checks that the coloring is proper before printing it, and exits with an error otherwise
+    #[structopt(long = "verify")]
+    pub verify: bool,
+
+    /// Stops branch and bound, tabu search, the evolutionary algorithm or simulated annealing after this many seconds or
+    /// on Ctrl-C, returning the best coloring found, and prints each improving coloring with its elapsed time in the
+    /// meantime
+    #[structopt(long = "time-limit", parse(try_from_str = parse_time_limit))]
+    pub time_limit: Option<Duration>,
+
+    /// Constructive algorithm giving the initial solution of tabu search, the evolutionary algorithm and simulated
+    /// annealing (glouton, rlf, welsh_powell, smallest_last or random_greedy)
+    #[structopt(long = "initial", alias = "tabu-initial", default_value = "glouton")]
+    pub initial_algorithm: Algorithm,
+
+    /// Iterations of each tabu search trying to remove a color
+    #[structopt(long = "tabu-iterations", default_value = "128")]
+    pub tabu_iteration_count: usize,
+
+    /// Tabu tenure added per conflicting edge
+    #[structopt(long = "tabu-alpha", default_value = "2")]
+    pub tabu_alpha: f64,
+
+    /// Largest random part of the tabu tenure, drawn from 1 to this value
+    #[structopt(long = "tabu-random-tenure", default_value = "9")]
+    pub tabu_random_tenure: usize,
+
+    /// Number of colorings in the population of the hybrid evolutionary algorithm
+    #[structopt(long = "population", default_value = "10")]
+    pub population_size: usize,
+
+    /// Offspring generated by the hybrid evolutionary algorithm while looking for each coloring, before it gives up
+    #[structopt(long = "generations", default_value = "1000")]
+    pub generation_count: usize,
+
+    /// Cooling schedule of simulated annealing (geometric or adaptive)
+    #[structopt(long = "cooling", default_value = "geometric")]
+    pub cooling_schedule: CoolingSchedule,
+
+    /// Factor by which the geometric cooling schedule multiplies the temperature
+    #[structopt(long = "cooling-rate", default_value = "0.95")]
+    pub cooling_rate: f64,
+
+    /// Initial temperature of simulated annealing, estimated from random moves by default
+    #[structopt(long = "initial-temperature")]
+    pub initial_temperature: Option<f64>,
+
+    /// Moves of simulated annealing at each temperature, 16 times the node count by default
+    #[structopt(long = "moves-per-temperature")]
+    pub moves_per_temperature: Option<usize>,
+
+    /// Times simulated annealing goes back to its initial temperature once frozen, before it stops
+    #[structopt(long = "reheats", default_value = "5")]
+    pub max_reheat_count: usize,
+
+    /// Seed of the random number generator, for reproducible runs
+    #[structopt(long = "seed")]
+    pub seed: Option<u64>,
+
+    /// Prints a lower bound on the number of colors, and whether the coloring is proven optimal
+    #[structopt(long = "show-bound")]
+    pub show_bound: bool,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Returns the algorithm and graph file, which are only required when no subcommand is given
+    pub fn solver_args(&self) -> (Algorithm, &Path) {
+        (
+            require_arg(self.algorithm, "-a <algorithm>"),
+            require_arg(self.filename.as_deref(), "-e <filename>"),
+        )
+    }
+
+    /// Returns the time limit of the solver, which constructive algorithms do not have since they never improve their
+    /// coloring
+    pub fn time_limit(&self, algorithm: Algorithm) -> Option<Duration> {
+        if self.time_limit.is_some() && algorithm.is_constructive() {
+            Error::with_description(
+                "Time limits are only available for branch_bound, tabou, hea and recuit",
+                ErrorKind::InvalidValue,
+            )
+            .exit()
+        }
+        self.time_limit
+    }
+
+    pub fn tabu_config(&self) -> TabuConfig {
+        TabuConfig {
+            initial_algorithm: self.initial_algorithm(),
+            max_iteration_count: self.tabu_iteration_count,
+            tenure_alpha: self.tabu_alpha,
+            tenure_random_max: self.tabu_random_tenure,
+            seed: self.seed,
+        }
+    }
+
+    pub fn evolutionary_config(&self) -> EvolutionaryConfig {
+        EvolutionaryConfig {
+            population_size: self.population_size,
+            max_generation_count: self.generation_count,
+            tabu: self.tabu_config(),
+        }
+    }
+
+    pub fn annealing_config(&self) -> AnnealingConfig {
+        // Otherwise the temperature never gets low enough for the annealing to freeze and stop
+        if !(self.cooling_rate > 0.0 && self.cooling_rate < 1.0) {
+            Error::with_description(
+                "The cooling rate must be strictly between 0 and 1",
+                ErrorKind::InvalidValue,
+            )
+            .exit()
+        }
+        if self
+            .initial_temperature
+            .is_some_and(|temperature| !(temperature.is_finite() && temperature > 0.0))
+        {
+            Error::with_description(
+                "The initial temperature must be positive and finite",
+                ErrorKind::InvalidValue,
+            )
+            .exit()
+        }
+
+        AnnealingConfig {
+            initial_algorithm: self.initial_algorithm(),
+            cooling_schedule: self.cooling_schedule,
+            cooling_rate: self.cooling_rate,
+            initial_temperature: self.initial_temperature,
+            moves_per_temperature: self.moves_per_temperature,
+            max_reheat_count: self.max_reheat_count,
+            seed: self.seed,
+        }
+    }
+
+    fn initial_algorithm(&self) -> Algorithm {
+        if !self.initial_algorithm.is_constructive() {
+            Error::with_description(
+                "The initial solution must come from a constructive algorithm",
+                ErrorKind::InvalidValue,
+            )
+            .exit()
+        }
+        self.initial_algorithm
+    }
+}
+
+fn require_arg<T>(value: Option<T>, name: &str) -> T {
+    value.unwrap_or_else(|| {
+        let message = format!("The following required argument was not provided: {}", name);
+        Error::with_description(&message, ErrorKind::MissingRequiredArgument).exit()
+    })
+}
+
+fn parse_time_limit(time_limit: &str) -> Result<Duration, String> {
+    let seconds: f64 = time_limit
+        .parse()
+        .map_err(|_| format!("Could not parse time limit: {}", time_limit))?;
+    match Duration::try_from_secs_f64(seconds) {
+        Ok(duration) if !duration.is_zero() => Ok(duration),
+        _ => Err("Time limit must be a positive and finite number of seconds".to_string()),
+    }
+}
+
+fn parse_color_count(color_count: &str) -> Result<usize, String> {
+    match color_count.parse() {
+        Ok(0) => Err("A coloring needs at least 1 color".to_string()),
+        Ok(color_count) => Ok(color_count),
+        Err(_) => Err(format!("Could not parse color count: {}", color_count)),
+    }
+}
+
+#[derive(StructOpt)]
+pub enum Command {
+    /// Checks a coloring file, in the format printed with -p, against a graph
+    Check(CheckArgs),
+    /// Writes whether a graph has a coloring with k colors as a SAT formula or an integer linear program
+    Export(ExportArgs),
+    /// Reads the model found by a SAT solver for an exported formula, and prints its coloring in the format printed
+    /// with -p
+    Import(ImportArgs),
+    /// Prints lower and upper bounds on the number of colors of a graph
+    Bounds(BoundsArgs),
+}
+
+#[derive(StructOpt)]
+pub struct CheckArgs {
+    /// Path to the graph file
+    #[structopt(short = "e", parse(from_os_str))]
+    pub filename: PathBuf,
+
+    /// Format of the graph file (auto, dimacs, matrix, edgelist or binary)
+    #[structopt(long = "format", default_value = "auto")]
+    pub format: GraphFormat,
+
+    /// Graph representation (auto, dense or sparse), chosen from the graph density by default
+    #[structopt(long = "representation", default_value = "auto")]
+    pub representation: GraphRepresentation,
+
+    /// Path to the coloring file
+    #[structopt(parse(from_os_str))]
+    pub coloring_filename: PathBuf,
+}
+
+#[derive(StructOpt)]
+pub struct ExportArgs {
+    /// Path to the graph file
+    #[structopt(short = "e", parse(from_os_str))]
+    pub filename: PathBuf,
+
+    /// Format of the graph file (auto, dimacs, matrix, edgelist or binary)
+    #[structopt(long = "format", default_value = "auto")]
+    pub format: GraphFormat,
+
+    /// Graph representation (auto, dense or sparse), chosen from the graph density by default
+    #[structopt(long = "representation", default_value = "auto")]
+    pub representation: GraphRepresentation,
+
+    /// Number of colors, one less than the greedy coloring by default
+    #[structopt(short = "k", parse(try_from_str = parse_color_count))]
+    pub color_count: Option<usize>,
+
+    /// Format of the model (cnf, lp or mps)
+    #[structopt(long = "model", default_value = "cnf")]
+    pub model_format: ModelFormat,
+
+    /// Path to the model file, printed by default
+    #[structopt(short = "o", parse(from_os_str))]
+    pub output_filename: Option<PathBuf>,
+}
+
+#[derive(StructOpt)]
+pub struct ImportArgs {
+    /// Path to the graph file
+    #[structopt(short = "e", parse(from_os_str))]
+    pub filename: PathBuf,
+
+    /// Format of the graph file (auto, dimacs, matrix, edgelist or binary)
+    #[structopt(long = "format", default_value = "auto")]
+    pub format: GraphFormat,
+
+    /// Graph representation (auto, dense or sparse), chosen from the graph density by default
+    #[structopt(long = "representation", default_value = "auto")]
+    pub representation: GraphRepresentation,
+
+    /// Number of colors of the exported formula, written in its comments
+    #[structopt(short = "k", parse(try_from_str = parse_color_count))]
+    pub color_count: usize,
+
+    /// Path to the SAT solver output
+    #[structopt(parse(from_os_str))]
+    pub model_filename: PathBuf,
+}
+
+#[derive(StructOpt)]
+pub struct BoundsArgs {
+    /// Path to the graph file
+    #[structopt(short = "e", parse(from_os_str))]
+    pub filename: PathBuf,
+
+    /// Format of the graph file (auto, dimacs, matrix, edgelist or binary)
+    #[structopt(long = "format", default_value = "auto")]
+    pub format: GraphFormat,
+
+    /// Graph representation (auto, dense or sparse), chosen from the graph density by default
+    #[structopt(long = "representation", default_value = "auto")]
+    pub representation: GraphRepresentation,
+
+    /// Prints the bounds as JSON
+    #[structopt(long = "json")]
+    pub json: bool,
 }
@@ -0,0 +1,120 @@
+use crate::graph::{Graph, NodeIndex};
+
+/// Largest graph whose maximum clique is searched exactly, larger graphs use a greedy clique
+pub const EXACT_CLIQUE_MAX_NODE_COUNT: usize = 128;
+
+/// Clique whose size is a lower bound on the number of colors, maximum on small graphs and maximal otherwise
+pub fn find_lower_bound_clique<G: Graph>(graph: &G) -> Vec<NodeIndex> {
+    if graph.node_count() <= EXACT_CLIQUE_MAX_NODE_COUNT {
+        find_maximum_clique(graph)
+    } else {
+        find_greedy_clique(graph)
+    }
+}
+
+/// Largest of the maximal cliques grown from each node, by adding the candidate of maximum degree
+pub fn find_greedy_clique<G: Graph>(graph: &G) -> Vec<NodeIndex> {
+    let mut best_clique = Vec::new();
+
+    for starting_node_index in graph.node_indices() {
+        // A clique containing the starting node cannot be larger than its degree plus one
+        if graph.degree(starting_node_index) < best_clique.len() {
+            continue;
+        }
+
+        let mut clique = vec![starting_node_index];
+        let mut candidates: Vec<_> = graph.neighbors(starting_node_index).collect();
+
+        while let Some(&node_index) = candidates
+            .iter()
+            .max_by_key(|&&node_index| graph.degree(node_index))
+        {
+            clique.push(node_index);
+            candidates.retain(|&candidate| graph.has_edge(node_index, candidate));
+        }
+
+        if clique.len() > best_clique.len() {
+            best_clique = clique;
+        }
+    }
+
+    best_clique
+}
+
+/// Maximum clique, found with a branch and bound where candidates are bounded by a greedy coloring
+pub fn find_maximum_clique<G: Graph>(graph: &G) -> Vec<NodeIndex> {
+    let mut best_clique = find_greedy_clique(graph);
+
+    // Nodes of large degree first, so that they get the small colors of the bound
+    let mut candidates: Vec<_> = graph.node_indices().collect();
+    candidates.sort_by_key(|&node_index| std::cmp::Reverse(graph.degree(node_index)));
+
+    expand_clique(graph, &mut Vec::new(), &candidates, &mut best_clique);
+
+    best_clique
+}
+
+fn expand_clique<G: Graph>(
+    graph: &G,
+    clique: &mut Vec<NodeIndex>,
+    candidates: &[NodeIndex],
+    best_clique: &mut Vec<NodeIndex>,
+) {
+    let (ordered_candidates, color_bounds) = sort_candidates_by_color(graph, candidates);
+
+    // Candidates with the largest colors first, stopping when even the colors cannot beat the best clique
+    for i in (0..ordered_candidates.len()).rev() {
+        if clique.len() + color_bounds[i] <= best_clique.len() {
+            return;
+        }
+
+        let node_index = ordered_candidates[i];
+        clique.push(node_index);
+
+        let new_candidates: Vec<_> = ordered_candidates[..i]
+            .iter()
+            .copied()
+            .filter(|&candidate| graph.has_edge(node_index, candidate))
+            .collect();
+
+        if new_candidates.is_empty() {
+            if clique.len() > best_clique.len() {
+                *best_clique = clique.clone();
+            }
+        } else {
+            expand_clique(graph, clique, &new_candidates, best_clique);
+        }
+
+        clique.pop();
+    }
+}
+
+/// Greedily colors the candidates, and returns them sorted by color with the number of colors used up to each one,
+/// which bounds the size of a clique among the candidates up to it
+fn sort_candidates_by_color<G: Graph>(
+    graph: &G,
+    candidates: &[NodeIndex],
+) -> (Vec<NodeIndex>, Vec<usize>) {
+    let mut color_classes: Vec<Vec<NodeIndex>> = Vec::new();
+
+    for &node_index in candidates {
+        let color_class = color_classes.iter_mut().find(|color_class| {
+            color_class
+                .iter()
+                .all(|&other_node_index| !graph.has_edge(node_index, other_node_index))
+        });
+        match color_class {
+            Some(color_class) => color_class.push(node_index),
+            None => color_classes.push(vec![node_index]),
+        }
+    }
+
+    let mut ordered_candidates = Vec::with_capacity(candidates.len());
+    let mut color_bounds = Vec::with_capacity(candidates.len());
+    for (color, color_class) in color_classes.into_iter().enumerate() {
+        color_bounds.extend(std::iter::repeat_n(color + 1, color_class.len()));
+        ordered_candidates.extend(color_class);
+    }
+
+    (ordered_candidates, color_bounds)
+}
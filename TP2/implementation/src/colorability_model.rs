@@ -0,0 +1,395 @@
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::coloring::Coloring;
+use crate::graph::{Graph, NodeIndex};
+
+/// Maximum number of terms per line in the LP format, whose lines have a limited length
+const LP_TERMS_PER_LINE: usize = 8;
+
+/// SAT variable, numbered from 1, which is true when the node has the color
+fn get_sat_variable(node_index: NodeIndex, color: usize, color_count: usize) -> usize {
+    node_index.index() * color_count + color + 1
+}
+
+/// Writes the k-colorability of the graph as a DIMACS CNF formula, where the nodes of the clique get the first colors
+/// to break the symmetry between colors
+pub fn write_cnf<G: Graph, W: Write>(
+    graph: &G,
+    color_count: usize,
+    clique: &[NodeIndex],
+    writer: &mut W,
+) -> io::Result<()> {
+    let node_count = graph.node_count();
+    let pair_count = color_count * color_count.saturating_sub(1) / 2;
+    let clause_count = node_count * (1 + pair_count)
+        + graph.edge_count() * color_count
+        + clique.len().min(color_count);
+
+    writeln!(
+        writer,
+        "c {}-colorability of a graph with {} nodes and {} edges",
+        color_count,
+        node_count,
+        graph.edge_count()
+    )?;
+    writeln!(
+        writer,
+        "c Variable n * {} + c + 1 is true when node n, numbered from 0, has color c",
+        color_count
+    )?;
+    writeln!(writer, "c Import the solver output with -k {}", color_count)?;
+    writeln!(
+        writer,
+        "p cnf {} {}",
+        node_count * color_count,
+        clause_count
+    )?;
+
+    for node_index in graph.node_indices() {
+        // Each node has at least one color
+        for color in 0..color_count {
+            write!(
+                writer,
+                "{} ",
+                get_sat_variable(node_index, color, color_count)
+            )?;
+        }
+        writeln!(writer, "0")?;
+
+        // Each node has at most one color
+        for color in 0..color_count {
+            for other_color in color + 1..color_count {
+                writeln!(
+                    writer,
+                    "-{} -{} 0",
+                    get_sat_variable(node_index, color, color_count),
+                    get_sat_variable(node_index, other_color, color_count)
+                )?;
+            }
+        }
+    }
+
+    // Neighbors have different colors
+    for (source_node_index, target_node_index) in graph.edges() {
+        for color in 0..color_count {
+            writeln!(
+                writer,
+                "-{} -{} 0",
+                get_sat_variable(source_node_index, color, color_count),
+                get_sat_variable(target_node_index, color, color_count)
+            )?;
+        }
+    }
+
+    for (color, &node_index) in clique.iter().take(color_count).enumerate() {
+        writeln!(
+            writer,
+            "{} 0",
+            get_sat_variable(node_index, color, color_count)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Reads a SAT solver model of a write_cnf formula, either in the competition format with "s" and "v" lines, or in
+/// the MiniSat format with a "SAT" line followed by the literals
+pub fn load_sat_model(
+    filename: &Path,
+    node_count: usize,
+    color_count: usize,
+) -> Result<Coloring, Box<dyn Error>> {
+    let content = fs::read_to_string(filename)?;
+
+    // Only the status line counts, since comment lines may mention unsatisfiability
+    let is_unsatisfiable = content.lines().any(|line| {
+        matches!(
+            line.split_whitespace().collect::<Vec<_>>().as_slice(),
+            ["UNSAT"] | ["s", "UNSATISFIABLE"]
+        )
+    });
+    if is_unsatisfiable {
+        return Err(format!(
+            "The formula is unsatisfiable: there is no {}-coloring",
+            color_count
+        )
+        .into());
+    }
+
+    let mut node_colors = Coloring::new(node_count);
+    for line in content.lines() {
+        let line = line.trim();
+        let literals = match line.split_whitespace().next() {
+            None | Some("c") | Some("s") | Some("SAT") => continue,
+            Some("v") => &line[1..],
+            Some(_) => line,
+        };
+
+        for literal in literals.split_whitespace() {
+            let literal: i64 = literal.parse()?;
+            if literal <= 0 {
+                continue;
+            }
+
+            let variable = literal as usize - 1;
+            if variable >= node_count * color_count {
+                return Err(format!(
+                    "Variable {} is out of range for {} nodes and {} colors",
+                    literal, node_count, color_count
+                )
+                .into());
+            }
+
+            // Keep the smallest color of each node
+            let node_index = NodeIndex::new(variable / color_count);
+            if node_colors.get(node_index).is_none() {
+                node_colors.set(node_index, variable % color_count);
+            }
+        }
+    }
+
+    Ok(node_colors)
+}
+
+/// Linear constraint sum(coefficient * variable) <sense> right_hand_side
+struct Constraint {
+    name: String,
+    terms: Vec<(usize, i64)>,
+    /// "E", "L" or "G", as in the MPS format
+    sense: &'static str,
+    right_hand_side: i64,
+}
+
+/// Integer linear program with binary variables, minimizing the sum of some variables
+struct LinearModel {
+    variable_names: Vec<String>,
+    objective_variables: Vec<usize>,
+    constraints: Vec<Constraint>,
+    /// Variables fixed to 1
+    fixed_variables: Vec<usize>,
+}
+
+impl LinearModel {
+    /// Assignment model: x_n_c is 1 when node n has color c, and w_c is 1 when color c is used, minimizing the number
+    /// of used colors. The nodes of the clique get the first colors to break the symmetry between colors.
+    fn new<G: Graph>(graph: &G, color_count: usize, clique: &[NodeIndex]) -> Self {
+        let node_variable =
+            |node_index: NodeIndex, color: usize| node_index.index() * color_count + color;
+        let color_variable = |color: usize| graph.node_count() * color_count + color;
+
+        let mut variable_names = Vec::with_capacity((graph.node_count() + 1) * color_count);
+        for node_index in graph.node_indices() {
+            for color in 0..color_count {
+                variable_names.push(format!("x_{}_{}", node_index.index(), color));
+            }
+        }
+        for color in 0..color_count {
+            variable_names.push(format!("w_{}", color));
+        }
+
+        let mut constraints = Vec::new();
+        for node_index in graph.node_indices() {
+            // Each node has exactly one color
+            constraints.push(Constraint {
+                name: format!("node_{}", node_index.index()),
+                terms: (0..color_count)
+                    .map(|color| (node_variable(node_index, color), 1))
+                    .collect(),
+                sense: "E",
+                right_hand_side: 1,
+            });
+
+            // Colors of nodes without neighbors are used, other nodes being covered by the edge constraints
+            if graph.degree(node_index) == 0 {
+                for color in 0..color_count {
+                    constraints.push(Constraint {
+                        name: format!("used_{}_{}", node_index.index(), color),
+                        terms: vec![
+                            (node_variable(node_index, color), 1),
+                            (color_variable(color), -1),
+                        ],
+                        sense: "L",
+                        right_hand_side: 0,
+                    });
+                }
+            }
+        }
+
+        // Neighbors have different colors, which are used
+        for (source_node_index, target_node_index) in graph.edges() {
+            for color in 0..color_count {
+                constraints.push(Constraint {
+                    name: format!(
+                        "edge_{}_{}_{}",
+                        source_node_index.index(),
+                        target_node_index.index(),
+                        color
+                    ),
+                    terms: vec![
+                        (node_variable(source_node_index, color), 1),
+                        (node_variable(target_node_index, color), 1),
+                        (color_variable(color), -1),
+                    ],
+                    sense: "L",
+                    right_hand_side: 0,
+                });
+            }
+        }
+
+        LinearModel {
+            variable_names,
+            objective_variables: (0..color_count).map(color_variable).collect(),
+            constraints,
+            fixed_variables: clique
+                .iter()
+                .take(color_count)
+                .enumerate()
+                .map(|(color, &node_index)| node_variable(node_index, color))
+                .collect(),
+        }
+    }
+
+    /// CPLEX LP format
+    fn write_lp<W: Write>(&self, title: &str, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "\\ {}", title)?;
+        writeln!(writer, "Minimize")?;
+        write!(writer, " colors:")?;
+        let objective_terms: Vec<_> = self
+            .objective_variables
+            .iter()
+            .map(|&variable| (variable, 1))
+            .collect();
+        self.write_lp_terms(&objective_terms, writer)?;
+        writeln!(writer)?;
+
+        writeln!(writer, "Subject To")?;
+        for constraint in &self.constraints {
+            write!(writer, " {}:", constraint.name)?;
+            self.write_lp_terms(&constraint.terms, writer)?;
+            let sense = match constraint.sense {
+                "E" => "=",
+                "L" => "<=",
+                _ => ">=",
+            };
+            writeln!(writer, " {} {}", sense, constraint.right_hand_side)?;
+        }
+
+        writeln!(writer, "Bounds")?;
+        for &variable in &self.fixed_variables {
+            writeln!(writer, " {} = 1", self.variable_names[variable])?;
+        }
+
+        writeln!(writer, "Binaries")?;
+        for variable_names in self.variable_names.chunks(LP_TERMS_PER_LINE) {
+            writeln!(writer, " {}", variable_names.join(" "))?;
+        }
+
+        writeln!(writer, "End")
+    }
+
+    fn write_lp_terms<W: Write>(&self, terms: &[(usize, i64)], writer: &mut W) -> io::Result<()> {
+        for (i, &(variable, coefficient)) in terms.iter().enumerate() {
+            if i > 0 && i % LP_TERMS_PER_LINE == 0 {
+                write!(writer, "\n  ")?;
+            }
+            let sign = if coefficient < 0 { "-" } else { "+" };
+            match coefficient.abs() {
+                1 => write!(writer, " {} {}", sign, self.variable_names[variable])?,
+                magnitude => write!(
+                    writer,
+                    " {} {} {}",
+                    sign, magnitude, self.variable_names[variable]
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Free MPS format, where every variable is binary
+    fn write_mps<W: Write>(&self, title: &str, writer: &mut W) -> io::Result<()> {
+        // Terms of each variable, as the MPS format lists the constraints by variable
+        let mut variable_terms = vec![Vec::new(); self.variable_names.len()];
+        for &variable in &self.objective_variables {
+            variable_terms[variable].push(("colors", 1));
+        }
+        for constraint in &self.constraints {
+            for &(variable, coefficient) in &constraint.terms {
+                variable_terms[variable].push((constraint.name.as_str(), coefficient));
+            }
+        }
+
+        writeln!(writer, "* {}", title)?;
+        writeln!(writer, "NAME coloring")?;
+
+        writeln!(writer, "ROWS")?;
+        writeln!(writer, " N colors")?;
+        for constraint in &self.constraints {
+            writeln!(writer, " {} {}", constraint.sense, constraint.name)?;
+        }
+
+        writeln!(writer, "COLUMNS")?;
+        writeln!(writer, " MARKER 'MARKER' 'INTORG'")?;
+        for (variable_name, terms) in self.variable_names.iter().zip(&variable_terms) {
+            for (row_name, coefficient) in terms {
+                writeln!(writer, " {} {} {}", variable_name, row_name, coefficient)?;
+            }
+        }
+        writeln!(writer, " MARKER 'MARKER' 'INTEND'")?;
+
+        writeln!(writer, "RHS")?;
+        for constraint in &self.constraints {
+            if constraint.right_hand_side != 0 {
+                writeln!(
+                    writer,
+                    " RHS {} {}",
+                    constraint.name, constraint.right_hand_side
+                )?;
+            }
+        }
+
+        writeln!(writer, "BOUNDS")?;
+        for variable_name in &self.variable_names {
+            writeln!(writer, " BV BND {}", variable_name)?;
+        }
+        for &variable in &self.fixed_variables {
+            writeln!(writer, " FX BND {} 1", self.variable_names[variable])?;
+        }
+
+        writeln!(writer, "ENDATA")
+    }
+}
+
+fn get_model_title<G: Graph>(graph: &G, color_count: usize) -> String {
+    format!(
+        "Coloring with at most {} colors of a graph with {} nodes and {} edges, where x_n_c is 1 when node n has \
+         color c",
+        color_count,
+        graph.node_count(),
+        graph.edge_count()
+    )
+}
+
+/// Writes the k-colorability of the graph as an assignment integer linear program in the LP format
+pub fn write_lp<G: Graph, W: Write>(
+    graph: &G,
+    color_count: usize,
+    clique: &[NodeIndex],
+    writer: &mut W,
+) -> io::Result<()> {
+    LinearModel::new(graph, color_count, clique)
+        .write_lp(&get_model_title(graph, color_count), writer)
+}
+
+/// Writes the k-colorability of the graph as an assignment integer linear program in the free MPS format
+pub fn write_mps<G: Graph, W: Write>(
+    graph: &G,
+    color_count: usize,
+    clique: &[NodeIndex],
+    writer: &mut W,
+) -> io::Result<()> {
+    LinearModel::new(graph, color_count, clique)
+        .write_mps(&get_model_title(graph, color_count), writer)
+}
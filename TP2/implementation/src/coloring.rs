@@ -0,0 +1,140 @@
+use crate::graph::{Graph, NodeIndex};
+
+/// Colors of the nodes of a graph, stored by node index, where uncolored nodes have no color
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coloring {
+    colors: Vec<Option<usize>>,
+    colored_node_count: usize,
+}
+
+impl Coloring {
+    /// Creates a coloring where every node is uncolored
+    pub fn new(node_count: usize) -> Self {
+        Coloring {
+            colors: vec![None; node_count],
+            colored_node_count: 0,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn get(&self, node_index: NodeIndex) -> Option<usize> {
+        self.colors[node_index.index()]
+    }
+
+    pub fn set(&mut self, node_index: NodeIndex, color: usize) {
+        let node_color = &mut self.colors[node_index.index()];
+        if node_color.is_none() {
+            self.colored_node_count += 1;
+        }
+        *node_color = Some(color);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.colored_node_count == self.colors.len()
+    }
+
+    /// Iterates over the nodes and their colors, in node index order
+    pub fn iter(&self) -> impl Iterator<Item = (NodeIndex, Option<usize>)> + '_ {
+        self.colors
+            .iter()
+            .enumerate()
+            .map(|(i, &color)| (NodeIndex::new(i), color))
+    }
+
+    /// Number of colors, which are numbered from 0 (the largest color plus one, or 0 if no node is colored)
+    pub fn color_count(&self) -> usize {
+        self.colors
+            .iter()
+            .flatten()
+            .max()
+            .map_or(0, |color| color.saturating_add(1))
+    }
+
+    /// Edges whose two nodes have the same color
+    pub fn conflicting_edges<G: Graph>(&self, graph: &G) -> Vec<(NodeIndex, NodeIndex)> {
+        graph
+            .edges()
+            .filter(|&(source_node_index, target_node_index)| {
+                let source_node_color = self.get(source_node_index);
+                source_node_color.is_some() && source_node_color == self.get(target_node_index)
+            })
+            .collect()
+    }
+
+    /// Whether every node of the graph is colored and no edge joins two nodes of the same color
+    pub fn is_proper<G: Graph>(&self, graph: &G) -> bool {
+        self.node_count() == graph.node_count()
+            && self.is_complete()
+            && self.conflicting_edges(graph).is_empty()
+    }
+
+    /// Renumbers the colors to 0..k, in order of first appearance by node index
+    pub fn normalize(&mut self) {
+        let mut new_colors = vec![None; self.color_count()];
+        let mut new_color_count = 0usize;
+
+        for color in self.colors.iter_mut().flatten() {
+            let new_color = new_colors[*color].get_or_insert_with(|| {
+                new_color_count += 1;
+                new_color_count - 1
+            });
+            *color = *new_color;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coloring_from_colors(colors: &[Option<usize>]) -> Coloring {
+        let mut node_colors = Coloring::new(colors.len());
+        for (i, &color) in colors.iter().enumerate() {
+            if let Some(color) = color {
+                node_colors.set(NodeIndex::new(i), color);
+            }
+        }
+        node_colors
+    }
+
+    fn colors(node_colors: &Coloring) -> Vec<Option<usize>> {
+        node_colors.iter().map(|(_, color)| color).collect()
+    }
+
+    #[test]
+    fn normalize_renumbers_colors_by_first_appearance() {
+        let mut node_colors = coloring_from_colors(&[Some(4), Some(2), Some(4), Some(7), Some(2)]);
+        node_colors.normalize();
+        assert_eq!(
+            colors(&node_colors),
+            vec![Some(0), Some(1), Some(0), Some(2), Some(1)]
+        );
+        assert_eq!(node_colors.color_count(), 3);
+    }
+
+    #[test]
+    fn normalize_keeps_uncolored_nodes() {
+        let mut node_colors = coloring_from_colors(&[None, Some(3), None, Some(1)]);
+        node_colors.normalize();
+        assert_eq!(colors(&node_colors), vec![None, Some(0), None, Some(1)]);
+        assert!(!node_colors.is_complete());
+    }
+
+    #[test]
+    fn normalize_keeps_normalized_coloring() {
+        let mut node_colors = coloring_from_colors(&[Some(0), Some(1), Some(0), Some(2)]);
+        let expected_node_colors = node_colors.clone();
+        node_colors.normalize();
+        assert_eq!(node_colors, expected_node_colors);
+    }
+
+    #[test]
+    fn normalize_handles_empty_coloring() {
+        let mut node_colors = Coloring::new(0);
+        node_colors.normalize();
+        assert_eq!(node_colors.color_count(), 0);
+    }
+}
@@ -0,0 +1,224 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::anytime::Anytime;
+use crate::coloring::Coloring;
+use crate::graph::{Graph, NodeIndex};
+use crate::graph_utils::create_rng;
+use crate::greedy_algorithm::solve_with_constructive_algorithm;
+use crate::tabu_search_algorithm::{run_tabucol, TabuConfig};
+
+/// Parameters of the hybrid evolutionary algorithm
+#[derive(Clone, Debug)]
+pub struct EvolutionaryConfig {
+    /// Number of colorings in the population
+    pub population_size: usize,
+    /// Number of offspring generated while trying to find each k-coloring, after which the algorithm stops
+    pub max_generation_count: usize,
+    /// Parameters of the tabu search improving each coloring, whose seed is also used for the whole algorithm, and
+    /// whose initial algorithm gives the first k
+    pub tabu: TabuConfig,
+}
+
+/// Coloring of the population, with its number of conflicting edges
+struct Individual {
+    node_colors: Coloring,
+    conflict_count: usize,
+}
+
+/// Hybrid evolutionary algorithm (Galinier and Hao): starting from a constructive coloring with k + 1 colors, looks for
+/// a k-coloring by evolving a population of k-colorings with conflicts, where each offspring of a Greedy Partition
+/// Crossover is improved by a tabu search, then tries again with one color less until no k-coloring is found
+pub fn solve_with_evolutionary_algorithm<G: Graph>(
+    graph: &G,
+    config: &EvolutionaryConfig,
+    anytime: &mut Anytime,
+) -> Coloring {
+    let mut rng = create_rng(config.tabu.seed);
+
+    // Get initial best solution using a constructive algorithm
+    let mut best_node_colors =
+        solve_with_constructive_algorithm(graph, config.tabu.initial_algorithm, &mut rng);
+    anytime.report(&best_node_colors);
+
+    while best_node_colors.color_count() > 1 {
+        match find_coloring(
+            graph,
+            best_node_colors.color_count() - 1,
+            config,
+            &mut rng,
+            anytime,
+        ) {
+            Some(mut node_colors) => {
+                // Renumber colors in case the coloring does not use every color
+                node_colors.normalize();
+                best_node_colors = node_colors;
+                anytime.report(&best_node_colors);
+            }
+            None => break, // If no coloring was found, the best solution is the previous one
+        }
+    }
+
+    best_node_colors
+}
+
+/// Evolves a population of colorings with color_count colors, until one has no conflicts
+fn find_coloring<G: Graph, R: Rng>(
+    graph: &G,
+    color_count: usize,
+    config: &EvolutionaryConfig,
+    rng: &mut R,
+    anytime: &Anytime,
+) -> Option<Coloring> {
+    // Create initial population
+    let mut population = Vec::with_capacity(config.population_size);
+    for _ in 0..config.population_size.max(2) {
+        let node_colors = color_with_random_order(graph, color_count, rng);
+        let individual =
+            improve_with_tabu_search(graph, &node_colors, color_count, config, rng, anytime);
+        if individual.conflict_count == 0 {
+            return Some(individual.node_colors);
+        }
+        population.push(individual);
+    }
+
+    for _ in 0..config.max_generation_count {
+        if anytime.should_stop() {
+            return None;
+        }
+
+        // Cross two random parents, and improve their offspring
+        let parent_indexes = rand::seq::index::sample(rng, population.len(), 2);
+        let (parent_index_1, parent_index_2) = (parent_indexes.index(0), parent_indexes.index(1));
+        let offspring_node_colors = cross_with_gpx(
+            &population[parent_index_1].node_colors,
+            &population[parent_index_2].node_colors,
+            color_count,
+            rng,
+        );
+        let offspring = improve_with_tabu_search(
+            graph,
+            &offspring_node_colors,
+            color_count,
+            config,
+            rng,
+            anytime,
+        );
+        if offspring.conflict_count == 0 {
+            return Some(offspring.node_colors);
+        }
+
+        // Replace the parent with the most conflicts
+        let replaced_index = if population[parent_index_1].conflict_count
+            >= population[parent_index_2].conflict_count
+        {
+            parent_index_1
+        } else {
+            parent_index_2
+        };
+        population[replaced_index] = offspring;
+    }
+
+    None
+}
+
+fn improve_with_tabu_search<G: Graph, R: Rng>(
+    graph: &G,
+    node_colors: &Coloring,
+    color_count: usize,
+    config: &EvolutionaryConfig,
+    rng: &mut R,
+    anytime: &Anytime,
+) -> Individual {
+    let (node_colors, conflict_count) =
+        run_tabucol(graph, node_colors, color_count, &config.tabu, rng, anytime);
+    Individual {
+        node_colors,
+        conflict_count,
+    }
+}
+
+/// Colors the nodes in random order, each with the smallest color not used by its neighbors, or with a random color
+/// if they all are
+fn color_with_random_order<G: Graph, R: Rng>(
+    graph: &G,
+    color_count: usize,
+    rng: &mut R,
+) -> Coloring {
+    let mut node_order: Vec<_> = graph.node_indices().collect();
+    node_order.shuffle(rng);
+
+    let mut node_colors = Coloring::new(graph.node_count());
+    let mut is_color_used = vec![false; color_count];
+    for node_index in node_order {
+        is_color_used
+            .iter_mut()
+            .for_each(|is_used| *is_used = false);
+        for neighbor_node_index in graph.neighbors(node_index) {
+            if let Some(color) = node_colors.get(neighbor_node_index) {
+                is_color_used[color] = true;
+            }
+        }
+
+        let color = is_color_used
+            .iter()
+            .position(|&is_used| !is_used)
+            .unwrap_or_else(|| rng.gen_range(0..color_count));
+        node_colors.set(node_index, color);
+    }
+
+    node_colors
+}
+
+/// Greedy Partition Crossover: alternately takes the largest color class of each parent, without the nodes already
+/// taken, as the next color class of the offspring. Nodes left once every color is used get a random color.
+fn cross_with_gpx<R: Rng>(
+    parent_1: &Coloring,
+    parent_2: &Coloring,
+    color_count: usize,
+    rng: &mut R,
+) -> Coloring {
+    let mut offspring = Coloring::new(parent_1.node_count());
+    let parents = [parent_1, parent_2];
+
+    for color in 0..color_count {
+        let parent = parents[color % 2];
+
+        // Count uncolored nodes of the offspring in each color class of the parent
+        let mut class_sizes = vec![0usize; color_count];
+        for (node_index, parent_color) in parent.iter() {
+            if offspring.get(node_index).is_none() {
+                class_sizes[parent_color.unwrap()] += 1;
+            }
+        }
+
+        let largest_class_color = (0..color_count)
+            .max_by_key(|&class_color| class_sizes[class_color])
+            .unwrap();
+        if class_sizes[largest_class_color] == 0 {
+            break;
+        }
+
+        let class_node_indexes: Vec<NodeIndex> = parent
+            .iter()
+            .filter(|&(node_index, parent_color)| {
+                parent_color == Some(largest_class_color) && offspring.get(node_index).is_none()
+            })
+            .map(|(node_index, _)| node_index)
+            .collect();
+        for node_index in class_node_indexes {
+            offspring.set(node_index, color);
+        }
+    }
+
+    let uncolored_node_indexes: Vec<_> = offspring
+        .iter()
+        .filter(|(_, color)| color.is_none())
+        .map(|(node_index, _)| node_index)
+        .collect();
+    for node_index in uncolored_node_indexes {
+        offspring.set(node_index, rng.gen_range(0..color_count));
+    }
+
+    offspring
+}
@@ -0,0 +1,192 @@
+use std::error::Error;
+
+use petgraph::matrix_graph::UnMatrix;
+
+use crate::cli_args::GraphRepresentation;
+
+pub type NodeIndex = petgraph::graph::NodeIndex<u32>;
+
+/// Dense adjacency matrix, which answers edge queries in constant time but needs O(n²) memory
+pub type DenseGraph = UnMatrix<(), (), Option<()>, u32>;
+
+/// Graphs denser than this are stored as an adjacency matrix, unless they have too many nodes
+const DENSE_MIN_DENSITY: f64 = 0.1;
+
+/// Largest graph stored as an adjacency matrix, which then takes about 100 MB
+const DENSE_MAX_NODE_COUNT: usize = 10_000;
+
+/// Largest graph stored as adjacency lists, so that a node count read from a file cannot exhaust memory
+const MAX_NODE_COUNT: usize = 10_000_000;
+
+/// Undirected graph without self-loops, whose nodes are numbered from 0
+pub trait Graph {
+    fn node_count(&self) -> usize;
+
+    fn edge_count(&self) -> usize;
+
+    /// Neighbors of a node, in increasing node index order
+    fn neighbors(&self, node_index: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_;
+
+    fn has_edge(&self, source_node_index: NodeIndex, target_node_index: NodeIndex) -> bool;
+
+    fn degree(&self, node_index: NodeIndex) -> usize {
+        self.neighbors(node_index).count()
+    }
+
+    fn node_indices(&self) -> impl Iterator<Item = NodeIndex> {
+        (0..self.node_count()).map(NodeIndex::new)
+    }
+
+    /// Every edge once, as a pair of nodes where the first node has the smaller index
+    fn edges(&self) -> impl Iterator<Item = (NodeIndex, NodeIndex)> + '_ {
+        self.node_indices().flat_map(move |source_node_index| {
+            self.neighbors(source_node_index)
+                .filter(move |&target_node_index| source_node_index < target_node_index)
+                .map(move |target_node_index| (source_node_index, target_node_index))
+        })
+    }
+}
+
+impl Graph for DenseGraph {
+    fn node_count(&self) -> usize {
+        UnMatrix::node_count(self)
+    }
+
+    fn edge_count(&self) -> usize {
+        UnMatrix::edge_count(self)
+    }
+
+    fn neighbors(&self, node_index: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_ {
+        UnMatrix::neighbors(self, node_index)
+    }
+
+    fn has_edge(&self, source_node_index: NodeIndex, target_node_index: NodeIndex) -> bool {
+        UnMatrix::has_edge(self, source_node_index, target_node_index)
+    }
+}
+
+/// Compressed sparse row adjacency lists, which need O(n + m) memory
+pub struct SparseGraph {
+    /// Neighbors of node i are neighbors[neighbor_offsets[i]..neighbor_offsets[i + 1]]
+    neighbor_offsets: Vec<usize>,
+    neighbors: Vec<NodeIndex>,
+}
+
+impl SparseGraph {
+    /// Creates a graph from its edges, ignoring self-loops and duplicate edges
+    pub fn from_edges(node_count: usize, edges: &[(usize, usize)]) -> Self {
+        let mut adjacency_lists = vec![Vec::new(); node_count];
+        for &(source, target) in edges {
+            if source != target {
+                adjacency_lists[source].push(NodeIndex::new(target));
+                adjacency_lists[target].push(NodeIndex::new(source));
+            }
+        }
+
+        let mut neighbor_offsets = Vec::with_capacity(node_count + 1);
+        let mut neighbors = Vec::new();
+        neighbor_offsets.push(0);
+        for mut adjacency_list in adjacency_lists {
+            adjacency_list.sort_unstable();
+            adjacency_list.dedup();
+            neighbors.extend(adjacency_list);
+            neighbor_offsets.push(neighbors.len());
+        }
+
+        SparseGraph {
+            neighbor_offsets,
+            neighbors,
+        }
+    }
+
+    fn neighbor_slice(&self, node_index: NodeIndex) -> &[NodeIndex] {
+        let i = node_index.index();
+        &self.neighbors[self.neighbor_offsets[i]..self.neighbor_offsets[i + 1]]
+    }
+}
+
+impl Graph for SparseGraph {
+    fn node_count(&self) -> usize {
+        self.neighbor_offsets.len() - 1
+    }
+
+    fn edge_count(&self) -> usize {
+        self.neighbors.len() / 2
+    }
+
+    fn neighbors(&self, node_index: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.neighbor_slice(node_index).iter().copied()
+    }
+
+    fn has_edge(&self, source_node_index: NodeIndex, target_node_index: NodeIndex) -> bool {
+        self.neighbor_slice(source_node_index)
+            .binary_search(&target_node_index)
+            .is_ok()
+    }
+
+    fn degree(&self, node_index: NodeIndex) -> usize {
+        self.neighbor_slice(node_index).len()
+    }
+}
+
+/// Graph stored with the representation chosen when loading it
+pub enum LoadedGraph {
+    Dense(DenseGraph),
+    Sparse(SparseGraph),
+}
+
+impl LoadedGraph {
+    /// Creates a graph from its edges, as an adjacency matrix or as adjacency lists depending on the representation,
+    /// ignoring self-loops and duplicate edges. Fails if the graph has too many nodes for the representation.
+    pub fn from_edges(
+        node_count: usize,
+        edges: &[(usize, usize)],
+        representation: GraphRepresentation,
+    ) -> Result<Self, Box<dyn Error>> {
+        if node_count > MAX_NODE_COUNT {
+            return Err(format!(
+                "Graph has {} nodes, more than the maximum of {}",
+                node_count, MAX_NODE_COUNT
+            )
+            .into());
+        }
+        if representation == GraphRepresentation::Dense && node_count > DENSE_MAX_NODE_COUNT {
+            return Err(format!(
+                "Graph has {} nodes, more than the maximum of {} for the dense representation",
+                node_count, DENSE_MAX_NODE_COUNT
+            )
+            .into());
+        }
+
+        let is_dense = match representation {
+            GraphRepresentation::Dense => true,
+            GraphRepresentation::Sparse => false,
+            GraphRepresentation::Auto => {
+                // Density computed on the edge count before removing duplicates, which is enough to choose
+                let max_edge_count = node_count * node_count.saturating_sub(1) / 2;
+                let density = edges.len() as f64 / max_edge_count.max(1) as f64;
+                node_count <= DENSE_MAX_NODE_COUNT && density >= DENSE_MIN_DENSITY
+            }
+        };
+
+        if !is_dense {
+            return Ok(LoadedGraph::Sparse(SparseGraph::from_edges(
+                node_count, edges,
+            )));
+        }
+
+        let mut graph = DenseGraph::with_capacity(node_count);
+
+        for _ in 0..node_count {
+            graph.add_node(());
+        }
+
+        for &(source, target) in edges {
+            if source != target {
+                graph.update_edge(NodeIndex::new(source), NodeIndex::new(target), ());
+            }
+        }
+
+        Ok(LoadedGraph::Dense(graph))
+    }
+}
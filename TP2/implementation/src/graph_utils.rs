@@ -1,72 +1,261 @@
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use std::fs::File;
+use std::ffi::OsStr;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
 use std::path::Path;
+use std::str;
 
-use itertools::Itertools;
-use petgraph::matrix_graph::{NodeIndex, UnMatrix};
-use petgraph::visit::IntoNodeIdentifiers;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-#[allow(dead_code)]
-pub fn load_graph_from_matrix(filename: &Path) -> Result<UnMatrix<(), ()>, Box<dyn Error>> {
-    let buffered = BufReader::new(File::open(filename)?);
-    let mut lines_it = buffered.lines().map(|l| l.unwrap());
+use crate::cli_args::{GraphFormat, GraphRepresentation};
+use crate::coloring::Coloring;
+use crate::graph::{Graph, LoadedGraph, NodeIndex};
 
-    // Read adjacency matrix size
-    let first_line = lines_it.next().unwrap();
-    let matrix_size = first_line.trim().parse()?;
+/// Loads a graph in the given format, detecting it from the extension then from the content for GraphFormat::Auto,
+/// and stores it with the given representation
+pub fn load_graph(
+    filename: &Path,
+    format: GraphFormat,
+    representation: GraphRepresentation,
+) -> Result<LoadedGraph, Box<dyn Error>> {
+    let content = fs::read(filename)?;
+
+    let format = match format {
+        GraphFormat::Auto => detect_graph_format(filename, &content),
+        format => format,
+    };
+
+    let (node_count, edges) = match format {
+        GraphFormat::DimacsBinary => load_graph_from_dimacs_binary(&content)?,
+        _ => {
+            let text = str::from_utf8(&content)?;
+            match format {
+                GraphFormat::Matrix => load_graph_from_matrix(text)?,
+                GraphFormat::EdgeList => load_graph_from_edge_list(text)?,
+                _ => load_graph_from_dimacs(text)?,
+            }
+        }
+    };
 
-    // Initialize graph
-    let mut graph = UnMatrix::with_capacity(matrix_size);
+    LoadedGraph::from_edges(node_count, &edges, representation)
+}
 
-    // Create nodes
-    for _ in 0..matrix_size {
-        graph.add_node(());
+fn detect_graph_format(filename: &Path, content: &[u8]) -> GraphFormat {
+    match filename.extension().and_then(OsStr::to_str) {
+        Some("col") => return GraphFormat::Dimacs,
+        Some("b") => return GraphFormat::DimacsBinary,
+        _ => {}
     }
 
-    // Create edges
+    // A binary file starts with the preamble length, followed by the preamble's DIMACS lines. Blank lines are skipped,
+    // since the text formats allow them before their first line.
+    let mut lines_it = content
+        .split(|&byte| byte == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace));
+    let first_line = String::from_utf8_lossy(lines_it.next().unwrap_or_default());
+    let first_tokens: Vec<&str> = first_line.split_whitespace().collect();
+
+    let is_second_line_dimacs = lines_it
+        .next()
+        .is_some_and(|line| line.starts_with(b"c") || line.starts_with(b"p"));
+
+    match first_tokens.as_slice() {
+        [first_token, ..] if ["c", "p", "e"].contains(first_token) => GraphFormat::Dimacs,
+        [_] if is_second_line_dimacs => GraphFormat::DimacsBinary,
+        [_] => GraphFormat::Matrix,
+        _ => GraphFormat::EdgeList,
+    }
+}
+
+/// Node count and edges of a graph file, where nodes are numbered from 0
+type GraphEdges = (usize, Vec<(usize, usize)>);
+
+/// Adjacency matrix: the node count, then one row of 0 and 1 per node
+fn load_graph_from_matrix(text: &str) -> Result<GraphEdges, Box<dyn Error>> {
+    let mut lines_it = text.lines().filter(|line| !line.trim().is_empty());
+
+    // Read adjacency matrix size
+    let first_line = lines_it.next().ok_or("Empty adjacency matrix file")?;
+    let matrix_size: usize = first_line.trim().parse()?;
+
+    // Read edges
+    let mut edges = Vec::new();
+    let mut row_count = 0usize;
     for (i, line) in lines_it.enumerate() {
-        for (j, number) in line.trim().split(char::is_whitespace).enumerate() {
-            let value: u8 = number.trim().parse()?;
-            if value == 1 {
-                graph.update_edge(NodeIndex::new(i), NodeIndex::new(j), ());
+        let values: Vec<&str> = line.split_whitespace().collect();
+        if i >= matrix_size || values.len() != matrix_size {
+            return Err(format!("Adjacency matrix is not {0}x{0}", matrix_size).into());
+        }
+
+        for (j, value) in values.into_iter().enumerate() {
+            match value {
+                "0" => {}
+                "1" => edges.push((i, j)),
+                _ => return Err(format!("Invalid adjacency matrix value: {}", value).into()),
             }
         }
+        row_count += 1;
     }
 
-    Ok(graph)
+    if row_count != matrix_size {
+        return Err(format!("Adjacency matrix is not {0}x{0}", matrix_size).into());
+    }
+
+    Ok((matrix_size, edges))
 }
 
-pub fn load_graph_from_edge_list(filename: &Path) -> Result<UnMatrix<(), ()>, Box<dyn Error>> {
-    let buffered = BufReader::new(File::open(filename)?);
-    let lines_it = buffered.lines().map(|l| l.unwrap());
+/// One edge per line as two node ids starting at 0, with lines starting with # or % as comments
+fn load_graph_from_edge_list(text: &str) -> Result<GraphEdges, Box<dyn Error>> {
+    let mut edges = Vec::new();
+    let mut node_count = 0usize;
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+            continue;
+        }
+
+        let node_ids = line
+            .split_whitespace()
+            .map(|s| s.parse())
+            .collect::<Result<Vec<usize>, _>>()?;
+        if node_ids.len() != 2 {
+            return Err(format!("Invalid edge list line: {}", line).into());
+        }
+
+        for &node_id in &node_ids {
+            let min_node_count = node_id
+                .checked_add(1)
+                .ok_or_else(|| format!("Edge list node {} is too large", node_id))?;
+            node_count = node_count.max(min_node_count);
+        }
+        edges.push((node_ids[0], node_ids[1]));
+    }
+
+    Ok((node_count, edges))
+}
+
+/// Parses the "p edge <node count> <edge count>" line of a DIMACS file
+fn parse_dimacs_problem_line(line: &str) -> Result<(usize, usize), Box<dyn Error>> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    match fields.as_slice() {
+        ["p", "edge" | "edges" | "col", node_count, edge_count] => {
+            Ok((node_count.parse()?, edge_count.parse()?))
+        }
+        _ => Err(format!("Invalid DIMACS problem line: {}", line).into()),
+    }
+}
+
+/// DIMACS .col format: comment lines (c), a problem line (p edge N M), then M edge lines (e u v) with nodes from 1 to N
+fn load_graph_from_dimacs(text: &str) -> Result<GraphEdges, Box<dyn Error>> {
+    let mut header = None;
+    let mut edges = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            None | Some("c") => {}
+            Some("p") => {
+                if header.is_some() {
+                    return Err("DIMACS file has several problem lines".into());
+                }
+                header = Some(parse_dimacs_problem_line(line)?);
+            }
+            Some("e") => {
+                let (node_count, _) = header.ok_or("DIMACS edge before the problem line")?;
+
+                let node_ids = fields
+                    .map(|s| s.parse())
+                    .collect::<Result<Vec<usize>, _>>()?;
+                if node_ids.len() != 2 {
+                    return Err(format!("Invalid DIMACS edge line: {}", line).into());
+                }
+                for &node_id in &node_ids {
+                    if node_id == 0 || node_id > node_count {
+                        return Err(format!(
+                            "DIMACS node {} is outside of 1..={}",
+                            node_id, node_count
+                        )
+                        .into());
+                    }
+                }
+
+                // Convert from 1-indexed to 0-indexed nodes
+                edges.push((node_ids[0] - 1, node_ids[1] - 1));
+            }
+            // Node descriptors of weighted variants do not affect coloring
+            Some("n") => {}
+            Some(_) => return Err(format!("Invalid DIMACS line: {}", line).into()),
+        }
+    }
+
+    let (node_count, edge_count) = header.ok_or("DIMACS file has no problem line")?;
+    if edges.len() != edge_count {
+        return Err(format!(
+            "DIMACS problem line declares {} edges, but the file has {}",
+            edge_count,
+            edges.len()
+        )
+        .into());
+    }
+
+    Ok((node_count, edges))
+}
+
+/// DIMACS binary format: the preamble length, the preamble with DIMACS comment and problem lines, then the lower
+/// triangle of the adjacency matrix, where row i has i + 1 bits padded to whole bytes, highest bit first
+fn load_graph_from_dimacs_binary(content: &[u8]) -> Result<GraphEdges, Box<dyn Error>> {
+    let first_line_end = content
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .ok_or("Missing DIMACS binary preamble length")?;
+    let preamble_length: usize = str::from_utf8(&content[..first_line_end])?.trim().parse()?;
+
+    let preamble_end = first_line_end + 1 + preamble_length;
+    let preamble = str::from_utf8(
+        content
+            .get(first_line_end + 1..preamble_end)
+            .ok_or("Truncated DIMACS binary preamble")?,
+    )?;
+    let problem_line = preamble
+        .lines()
+        .find(|line| line.starts_with('p'))
+        .ok_or("DIMACS binary preamble has no problem line")?;
+    let (node_count, edge_count) = parse_dimacs_problem_line(problem_line)?;
 
-    // Parse edges
     let mut edges = Vec::new();
-    for line in lines_it {
-        if line.starts_with("e") {
-            let edge_indices: Vec<u16> = line
-                .split(" ")
-                .skip(1)
-                .take(2)
-                .map(|s| s.parse())
-                .collect::<Result<Vec<_>, _>>()?;
-            edges.push((edge_indices[0] - 1, edge_indices[1] - 1)); // Convert from 1-indexed to 0-indexed nodes
+    let mut row_start = preamble_end;
+    for i in 0..node_count {
+        let row_length = (i + 8) / 8;
+        let row = content
+            .get(row_start..row_start + row_length)
+            .ok_or("Truncated DIMACS binary adjacency matrix")?;
+        for j in 0..i {
+            if row[j / 8] & (0x80 >> (j % 8)) != 0 {
+                edges.push((i, j));
+            }
         }
+        row_start += row_length;
     }
 
-    // Create graph
-    let graph = UnMatrix::from_edges(edges);
+    if edges.len() != edge_count {
+        return Err(format!(
+            "DIMACS problem line declares {} edges, but the adjacency matrix has {}",
+            edge_count,
+            edges.len()
+        )
+        .into());
+    }
 
-    Ok(graph)
+    Ok((node_count, edges))
 }
 
-pub fn get_node_degrees(graph: &UnMatrix<(), ()>) -> HashMap<NodeIndex, usize> {
+pub fn get_node_degrees<G: Graph>(graph: &G) -> HashMap<NodeIndex, usize> {
     let mut node_degrees = HashMap::new();
 
-    for node_index in graph.node_identifiers() {
-        let degree = graph.edges(node_index).count();
+    for node_index in graph.node_indices() {
+        let degree = graph.degree(node_index);
         node_degrees.insert(node_index, degree);
     }
 
@@ -88,34 +277,298 @@ pub fn find_node_with_maximum_degree(node_degrees: &HashMap<NodeIndex, usize>) -
     max_degree_node_index
 }
 
-pub fn get_neighbor_unique_colors(
-    graph: &UnMatrix<(), ()>,
+pub fn get_neighbor_unique_colors<G: Graph>(
+    graph: &G,
     node_index: NodeIndex,
-    node_colors: &HashMap<NodeIndex, usize>,
+    node_colors: &Coloring,
 ) -> HashSet<usize> {
     let mut unique_neighbor_colors = HashSet::new();
 
     for neighbor_node_index in graph.neighbors(node_index) {
-        if let Some(color) = node_colors.get(&neighbor_node_index) {
-            unique_neighbor_colors.insert(*color);
+        if let Some(color) = node_colors.get(neighbor_node_index) {
+            unique_neighbor_colors.insert(color);
         }
     }
 
     unique_neighbor_colors
 }
 
-pub fn count_colors(node_colors: &HashMap<NodeIndex, usize>) -> usize {
-    node_colors.values().max().unwrap() + 1
+/// Random number generator seeded with the given seed, or from the OS if there is none
+pub fn create_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
 }
 
-pub fn print_result(node_colors: &HashMap<NodeIndex, usize>) {
-    println!("{}", count_colors(node_colors));
+pub fn print_result(node_colors: &Coloring) {
+    println!("{}", node_colors.color_count());
+    println!("{}", format_coloring(node_colors));
+}
 
-    let color_str = node_colors
+/// Colors of the nodes separated by spaces, in node index order
+pub fn format_coloring(node_colors: &Coloring) -> String {
+    node_colors
         .iter()
-        .sorted()
-        .map(|(_node_index, color)| color.to_string())
+        .map(|(_node_index, color)| {
+            color
+                .expect("Cannot print an incomplete coloring")
+                .to_string()
+        })
         .collect::<Vec<_>>()
-        .join(" ");
-    println!("{}", color_str);
+        .join(" ")
+}
+
+/// Loads a coloring in the print_result format: the color count, then the color of each node on one line.
+/// Nodes missing from the end of the line are left uncolored.
+pub fn load_coloring(
+    filename: &Path,
+    node_count: usize,
+) -> Result<(usize, Coloring), Box<dyn Error>> {
+    let buffered = BufReader::new(File::open(filename)?);
+    let mut lines_it = buffered.lines();
+
+    let first_line = lines_it.next().ok_or("Empty coloring file")??;
+    let declared_color_count = first_line.trim().parse()?;
+
+    let mut node_colors = Coloring::new(node_count);
+    if let Some(line) = lines_it.next() {
+        let colors: Vec<usize> = line?
+            .split_whitespace()
+            .map(|s| s.parse())
+            .collect::<Result<_, _>>()?;
+        if colors.len() > node_count {
+            return Err(format!(
+                "Coloring has {} colors for a graph of {} nodes",
+                colors.len(),
+                node_count
+            )
+            .into());
+        }
+
+        for (i, color) in colors.into_iter().enumerate() {
+            node_colors.set(NodeIndex::new(i), color);
+        }
+    }
+
+    Ok((declared_color_count, node_colors))
+}
+
+/// Describes every problem of a coloring: conflicting edges, uncolored nodes, colors past the declared color count,
+/// and a declared color count that differs from the number of colors used. The coloring is proper if there is none.
+pub fn find_coloring_errors<G: Graph>(
+    graph: &G,
+    node_colors: &Coloring,
+    declared_color_count: Option<usize>,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for (source_node_index, target_node_index) in node_colors.conflicting_edges(graph) {
+        errors.push(format!(
+            "Conflicting edge: nodes {} and {} both have color {}",
+            source_node_index.index() + 1,
+            target_node_index.index() + 1,
+            node_colors.get(source_node_index).unwrap()
+        ));
+    }
+
+    // Colors come from the file, so they are counted without allocating anything per color
+    let mut used_colors = HashSet::new();
+    for (node_index, color) in node_colors.iter() {
+        match color {
+            None => errors.push(format!("Uncolored node: {}", node_index.index() + 1)),
+            Some(color) => {
+                used_colors.insert(color);
+                if declared_color_count
+                    .is_some_and(|declared_color_count| color >= declared_color_count)
+                {
+                    errors.push(format!(
+                        "Color out of range: node {} has color {}, but the header declares {} colors",
+                        node_index.index() + 1,
+                        color,
+                        declared_color_count.unwrap()
+                    ));
+                }
+            }
+        }
+    }
+
+    let used_color_count = used_colors.len();
+    if let Some(declared_color_count) = declared_color_count {
+        if declared_color_count != used_color_count {
+            errors.push(format!(
+                "Color count mismatch: header declares {} colors but {} are used",
+                declared_color_count, used_color_count
+            ));
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::SparseGraph;
+
+    /// Encodes a graph in the DIMACS binary format, with the lower triangle rows padded to whole bytes
+    fn encode_dimacs_binary(
+        node_count: usize,
+        edges: &[(usize, usize)],
+        edge_count: usize,
+    ) -> Vec<u8> {
+        let preamble = format!("c test graph\np edge {} {}\n", node_count, edge_count);
+        let mut content = format!("{}\n{}", preamble.len(), preamble).into_bytes();
+        for i in 0..node_count {
+            let mut row = vec![0u8; (i + 8) / 8];
+            for &(source, target) in edges {
+                let (i_edge, j) = if source > target {
+                    (source, target)
+                } else {
+                    (target, source)
+                };
+                if i_edge == i {
+                    row[j / 8] |= 0x80 >> (j % 8);
+                }
+            }
+            content.extend(row);
+        }
+        content
+    }
+
+    fn sorted_edges((node_count, mut edges): GraphEdges) -> GraphEdges {
+        for edge in &mut edges {
+            *edge = (edge.0.min(edge.1), edge.0.max(edge.1));
+        }
+        edges.sort_unstable();
+        edges.dedup();
+        (node_count, edges)
+    }
+
+    /// Loads a coloring file written to the temporary directory, as the check subcommand does
+    fn load_coloring_text(name: &str, text: &str, node_count: usize) -> (usize, Coloring) {
+        let filename = std::env::temp_dir().join(format!("tp_{}_{}.txt", name, std::process::id()));
+        fs::write(&filename, text).unwrap();
+        let result = load_coloring(&filename, node_count);
+        fs::remove_file(&filename).unwrap();
+        result.unwrap()
+    }
+
+    #[test]
+    fn check_reports_out_of_range_colors() {
+        let graph = SparseGraph::from_edges(3, &[(0, 1), (1, 2)]);
+        for color in ["1000000000000000000", "18446744073709551615"] {
+            let text = format!("3\n0 1 {}\n", color);
+            let (declared_color_count, node_colors) = load_coloring_text("out_of_range", &text, 3);
+            let errors = find_coloring_errors(&graph, &node_colors, Some(declared_color_count));
+            assert_eq!(
+                errors,
+                vec![format!(
+                    "Color out of range: node 3 has color {}, but the header declares 3 colors",
+                    color
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn check_accepts_proper_coloring() {
+        let graph = SparseGraph::from_edges(3, &[(0, 1), (1, 2)]);
+        let (declared_color_count, node_colors) = load_coloring_text("proper", "2\n0 1 0\n", 3);
+        assert!(find_coloring_errors(&graph, &node_colors, Some(declared_color_count)).is_empty());
+    }
+
+    #[test]
+    fn dimacs_keeps_trailing_isolated_nodes() {
+        let text = "c comment\np edge 5 2\ne 1 2\ne 2 3\n";
+        assert_eq!(
+            load_graph_from_dimacs(text).unwrap(),
+            (5, vec![(0, 1), (1, 2)])
+        );
+    }
+
+    #[test]
+    fn dimacs_accepts_tabs() {
+        let text = "p\tedge\t3\t1\ne\t1\t3\n";
+        assert_eq!(load_graph_from_dimacs(text).unwrap(), (3, vec![(0, 2)]));
+    }
+
+    #[test]
+    fn dimacs_rejects_node_zero() {
+        assert!(load_graph_from_dimacs("p edge 3 1\ne 0 1\n").is_err());
+    }
+
+    #[test]
+    fn dimacs_rejects_node_past_count() {
+        assert!(load_graph_from_dimacs("p edge 3 1\ne 1 4\n").is_err());
+    }
+
+    #[test]
+    fn dimacs_rejects_edge_count_mismatch() {
+        assert!(load_graph_from_dimacs("p edge 3 2\ne 1 2\n").is_err());
+        assert!(load_graph_from_dimacs("p edge 3 1\ne 1 2\ne 2 3\n").is_err());
+    }
+
+    #[test]
+    fn matrix_keeps_trailing_isolated_nodes() {
+        let text = "3\n0 1 0\n1 0 0\n0 0 0\n";
+        assert_eq!(
+            sorted_edges(load_graph_from_matrix(text).unwrap()),
+            (3, vec![(0, 1)])
+        );
+    }
+
+    #[test]
+    fn matrix_accepts_tabs() {
+        let text = "2\n0\t1\n1\t0\n";
+        assert_eq!(
+            sorted_edges(load_graph_from_matrix(text).unwrap()),
+            (2, vec![(0, 1)])
+        );
+    }
+
+    #[test]
+    fn matrix_rejects_size_mismatch() {
+        assert!(load_graph_from_matrix("3\n0 1 0\n1 0 0\n").is_err());
+        assert!(load_graph_from_matrix("2\n0 1 0\n1 0 0\n").is_err());
+        assert!(load_graph_from_matrix("2\n0 1\n1 0\n0 0\n").is_err());
+    }
+
+    #[test]
+    fn matrix_rejects_invalid_values() {
+        assert!(load_graph_from_matrix("2\n0 2\n2 0\n").is_err());
+    }
+
+    #[test]
+    fn dimacs_binary_keeps_trailing_isolated_nodes() {
+        let edges = [(1, 0), (9, 3)];
+        let content = encode_dimacs_binary(12, &edges, 2);
+        assert_eq!(
+            sorted_edges(load_graph_from_dimacs_binary(&content).unwrap()),
+            (12, vec![(0, 1), (3, 9)])
+        );
+    }
+
+    #[test]
+    fn dimacs_binary_rejects_edge_count_mismatch() {
+        let content = encode_dimacs_binary(4, &[(1, 0), (3, 2)], 3);
+        assert!(load_graph_from_dimacs_binary(&content).is_err());
+    }
+
+    #[test]
+    fn dimacs_binary_rejects_truncated_matrix() {
+        let mut content = encode_dimacs_binary(10, &[(9, 0)], 1);
+        content.pop();
+        assert!(load_graph_from_dimacs_binary(&content).is_err());
+    }
+
+    #[test]
+    fn detection_skips_leading_blank_lines() {
+        let filename = Path::new("graph");
+        assert!(detect_graph_format(filename, b"\n\np edge 2 1\ne 1 2\n") == GraphFormat::Dimacs);
+        assert!(detect_graph_format(filename, b"\n  \n2\n0 1\n1 0\n") == GraphFormat::Matrix);
+        assert!(detect_graph_format(filename, b"\n0 1\n") == GraphFormat::EdgeList);
+        let content = encode_dimacs_binary(2, &[(1, 0)], 1);
+        assert!(detect_graph_format(filename, &content) == GraphFormat::DimacsBinary);
+    }
 }
@@ -1,24 +1,32 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 
-use petgraph::matrix_graph::{NodeIndex, UnMatrix};
-use petgraph::visit::IntoNodeIdentifiers;
+use rand::seq::SliceRandom;
+use rand::Rng;
 
+use crate::cli_args::Algorithm;
+use crate::coloring::Coloring;
+use crate::graph::{Graph, NodeIndex};
 use crate::graph_utils::{
     find_node_with_maximum_degree, get_neighbor_unique_colors, get_node_degrees,
 };
 
-pub fn solve_with_greedy(graph: &UnMatrix<(), ()>) -> HashMap<NodeIndex, usize> {
+pub fn solve_with_greedy<G: Graph>(graph: &G) -> Coloring {
     let node_degrees = get_node_degrees(graph);
 
-    let mut node_colors = HashMap::new();
+    let mut node_colors = Coloring::new(graph.node_count());
     let mut color_count = 1usize;
 
+    if graph.node_count() == 0 {
+        return node_colors;
+    }
+
     // Color starting node
     let starting_node_index = find_node_with_maximum_degree(&node_degrees);
-    node_colors.insert(starting_node_index, 0);
+    node_colors.set(starting_node_index, 0);
 
     // Color all nodes
-    while node_colors.len() < graph.node_count() {
+    while !node_colors.is_complete() {
         // Get next uncolored node to color
         let current_node_index = find_node_with_greedy_choice(graph, &node_degrees, &node_colors);
 
@@ -26,7 +34,7 @@ pub fn solve_with_greedy(graph: &UnMatrix<(), ()>) -> HashMap<NodeIndex, usize>
         let color =
             get_smallest_color_for_node(graph, current_node_index, &node_colors, color_count)
                 .unwrap();
-        node_colors.insert(current_node_index, color);
+        node_colors.set(current_node_index, color);
 
         // Increment color count if color assigned to node was greater than any current color
         let is_node_color_new = color == color_count;
@@ -38,16 +46,15 @@ pub fn solve_with_greedy(graph: &UnMatrix<(), ()>) -> HashMap<NodeIndex, usize>
     node_colors
 }
 
-pub fn find_node_with_greedy_choice(
-    graph: &UnMatrix<(), ()>,
+pub fn find_node_with_greedy_choice<G: Graph>(
+    graph: &G,
     node_degrees: &HashMap<NodeIndex, usize>,
-    node_colors: &HashMap<NodeIndex, usize>,
+    node_colors: &Coloring,
 ) -> NodeIndex {
     // Compute remaining uncolored nodes
-    let colored_node_set: HashSet<_> = node_colors.keys().cloned().collect();
     let uncolored_node_indexes: Vec<_> = graph
-        .node_identifiers()
-        .filter(|x| !colored_node_set.contains(x))
+        .node_indices()
+        .filter(|&x| node_colors.get(x).is_none())
         .collect();
 
     // Find node with max degree of saturation among uncolored nodes, with max number of neighbors in case of equality
@@ -74,19 +81,252 @@ pub fn find_node_with_greedy_choice(
     max_saturation_node_index
 }
 
-fn get_smallest_color_for_node(
-    graph: &UnMatrix<(), ()>,
+fn get_smallest_color_for_node<G: Graph>(
+    graph: &G,
     node_index: NodeIndex,
-    node_colors: &HashMap<NodeIndex, usize>,
+    node_colors: &Coloring,
     color_count: usize,
 ) -> Option<usize> {
     let neighbor_colors = get_neighbor_unique_colors(graph, node_index, node_colors);
 
-    for i in 0..color_count + 1 {
-        if !neighbor_colors.contains(&i) {
-            return Some(i);
+    (0..color_count + 1).find(|i| !neighbor_colors.contains(i))
+}
+
+/// Solves with one of the constructive algorithms, which color each node once without backtracking
+pub fn solve_with_constructive_algorithm<G: Graph, R: Rng>(
+    graph: &G,
+    algorithm: Algorithm,
+    rng: &mut R,
+) -> Coloring {
+    match algorithm {
+        Algorithm::Greedy => solve_with_greedy(graph),
+        Algorithm::RecursiveLargestFirst => solve_with_recursive_largest_first(graph),
+        Algorithm::WelshPowell => solve_with_welsh_powell(graph),
+        Algorithm::SmallestLast => solve_with_smallest_last(graph),
+        Algorithm::RandomGreedy => solve_with_random_greedy(graph, rng),
+        _ => panic!("Not a constructive algorithm"),
+    }
+}
+
+/// Welsh-Powell: colors the nodes by decreasing degree, with smaller index in case of equality
+pub fn solve_with_welsh_powell<G: Graph>(graph: &G) -> Coloring {
+    let mut node_order: Vec<_> = graph.node_indices().collect();
+    node_order.sort_by_key(|&node_index| Reverse(graph.degree(node_index)));
+
+    color_nodes_in_order(graph, &node_order)
+}
+
+/// Smallest-last: colors the nodes in the reverse order of their removal, when repeatedly removing a node of minimum
+/// degree, so that each node has at most degeneracy colored neighbors when it gets colored
+pub fn solve_with_smallest_last<G: Graph>(graph: &G) -> Coloring {
+    let mut node_order = find_degeneracy_order(graph);
+    node_order.reverse();
+
+    color_nodes_in_order(graph, &node_order)
+}
+
+/// Colors the nodes in random order
+pub fn solve_with_random_greedy<G: Graph, R: Rng>(graph: &G, rng: &mut R) -> Coloring {
+    let mut node_order: Vec<_> = graph.node_indices().collect();
+    node_order.shuffle(rng);
+
+    color_nodes_in_order(graph, &node_order)
+}
+
+/// Recursive Largest First: builds one color class at a time, starting from the uncolored node with the most uncolored
+/// neighbors, then adding the node with the most neighbors excluded from the class, and the fewest neighbors still
+/// candidates in case of equality
+pub fn solve_with_recursive_largest_first<G: Graph>(graph: &G) -> Coloring {
+    let mut node_colors = Coloring::new(graph.node_count());
+
+    // Candidates for the current color among uncolored nodes, the others being excluded because of a neighbor in it,
+    // with the number of neighbors of each candidate that are candidates or excluded
+    let mut is_candidate = vec![false; graph.node_count()];
+    let mut candidate_neighbor_counts = vec![0usize; graph.node_count()];
+    let mut excluded_neighbor_counts = vec![0usize; graph.node_count()];
+
+    // Candidates whose counts changed when adding a node to the color class
+    let mut is_changed = vec![false; graph.node_count()];
+    let mut changed_node_indexes = Vec::new();
+
+    let mut color = 0usize;
+    while !node_colors.is_complete() {
+        let uncolored_node_indexes: Vec<_> = graph
+            .node_indices()
+            .filter(|&node_index| node_colors.get(node_index).is_none())
+            .collect();
+
+        // Every uncolored node is a candidate for the new color
+        for &node_index in &uncolored_node_indexes {
+            is_candidate[node_index.index()] = true;
+            excluded_neighbor_counts[node_index.index()] = 0;
+        }
+        for &node_index in &uncolored_node_indexes {
+            candidate_neighbor_counts[node_index.index()] = graph
+                .neighbors(node_index)
+                .filter(|&neighbor_node_index| is_candidate[neighbor_node_index.index()])
+                .count();
+        }
+
+        // Candidates ordered by key, where a candidate may also appear with former counts
+        let mut candidate_heap: BinaryHeap<_> = uncolored_node_indexes
+            .iter()
+            .map(|&node_index| {
+                get_candidate_key(
+                    node_index,
+                    &excluded_neighbor_counts,
+                    &candidate_neighbor_counts,
+                )
+            })
+            .collect();
+
+        let mut next_node_index =
+            uncolored_node_indexes
+                .iter()
+                .copied()
+                .max_by_key(|&node_index| {
+                    (
+                        candidate_neighbor_counts[node_index.index()],
+                        Reverse(node_index),
+                    )
+                });
+
+        while let Some(node_index) = next_node_index {
+            // Add node to the color class, and exclude its candidate neighbors from it
+            node_colors.set(node_index, color);
+            is_candidate[node_index.index()] = false;
+
+            for neighbor_node_index in graph.neighbors(node_index) {
+                if !is_candidate[neighbor_node_index.index()] {
+                    continue;
+                }
+                is_candidate[neighbor_node_index.index()] = false;
+
+                for second_neighbor_node_index in graph.neighbors(neighbor_node_index) {
+                    if is_candidate[second_neighbor_node_index.index()] {
+                        candidate_neighbor_counts[second_neighbor_node_index.index()] -= 1;
+                        excluded_neighbor_counts[second_neighbor_node_index.index()] += 1;
+                        if !is_changed[second_neighbor_node_index.index()] {
+                            is_changed[second_neighbor_node_index.index()] = true;
+                            changed_node_indexes.push(second_neighbor_node_index);
+                        }
+                    }
+                }
+            }
+
+            // Push the new keys once per changed candidate
+            for changed_node_index in changed_node_indexes.drain(..) {
+                is_changed[changed_node_index.index()] = false;
+                if is_candidate[changed_node_index.index()] {
+                    candidate_heap.push(get_candidate_key(
+                        changed_node_index,
+                        &excluded_neighbor_counts,
+                        &candidate_neighbor_counts,
+                    ));
+                }
+            }
+
+            // Get next candidate, skipping nodes that are no longer candidates or whose counts have changed since
+            next_node_index = None;
+            while let Some(key) = candidate_heap.pop() {
+                let Reverse(candidate_node_index) = key.2;
+                if is_candidate[candidate_node_index.index()]
+                    && key
+                        == get_candidate_key(
+                            candidate_node_index,
+                            &excluded_neighbor_counts,
+                            &candidate_neighbor_counts,
+                        )
+                {
+                    next_node_index = Some(candidate_node_index);
+                    break;
+                }
+            }
+        }
+
+        color += 1;
+    }
+
+    node_colors
+}
+
+/// Key of a Recursive Largest First candidate, the largest key being chosen: most excluded neighbors, then fewest
+/// candidate neighbors, then smallest index
+fn get_candidate_key(
+    node_index: NodeIndex,
+    excluded_neighbor_counts: &[usize],
+    candidate_neighbor_counts: &[usize],
+) -> (usize, Reverse<usize>, Reverse<NodeIndex>) {
+    (
+        excluded_neighbor_counts[node_index.index()],
+        Reverse(candidate_neighbor_counts[node_index.index()]),
+        Reverse(node_index),
+    )
+}
+
+/// Order in which the nodes are removed when repeatedly removing a node of minimum degree in the remaining graph
+pub fn find_degeneracy_order<G: Graph>(graph: &G) -> Vec<NodeIndex> {
+    let mut degrees: Vec<_> = graph
+        .node_indices()
+        .map(|node_index| graph.degree(node_index))
+        .collect();
+    let mut is_removed = vec![false; graph.node_count()];
+
+    // Nodes by degree, where a node may also appear in buckets of its former degrees
+    let mut degree_buckets = vec![Vec::new(); graph.node_count()];
+    for node_index in graph.node_indices() {
+        degree_buckets[degrees[node_index.index()]].push(node_index);
+    }
+
+    let mut node_order = Vec::with_capacity(graph.node_count());
+    let mut min_degree = 0usize;
+    while node_order.len() < graph.node_count() {
+        let node_index = match degree_buckets[min_degree].pop() {
+            Some(node_index) => node_index,
+            None => {
+                min_degree += 1;
+                continue;
+            }
+        };
+        if is_removed[node_index.index()] || degrees[node_index.index()] != min_degree {
+            continue;
+        }
+
+        is_removed[node_index.index()] = true;
+        node_order.push(node_index);
+
+        for neighbor_node_index in graph.neighbors(node_index) {
+            if !is_removed[neighbor_node_index.index()] {
+                degrees[neighbor_node_index.index()] -= 1;
+                degree_buckets[degrees[neighbor_node_index.index()]].push(neighbor_node_index);
+            }
+        }
+
+        // Removing the node decreased the degree of its neighbors by one at most
+        min_degree = min_degree.saturating_sub(1);
+    }
+
+    node_order
+}
+
+/// Colors the nodes in the given order, each with the smallest color not used by its neighbors
+fn color_nodes_in_order<G: Graph>(graph: &G, node_order: &[NodeIndex]) -> Coloring {
+    let mut node_colors = Coloring::new(graph.node_count());
+
+    for &node_index in node_order {
+        // A node has at most degree neighbors, so one of the first degree + 1 colors is free
+        let mut is_color_used = vec![false; graph.degree(node_index) + 1];
+        for neighbor_node_index in graph.neighbors(node_index) {
+            if let Some(color) = node_colors.get(neighbor_node_index) {
+                if color < is_color_used.len() {
+                    is_color_used[color] = true;
+                }
+            }
         }
+
+        let color = is_color_used.iter().position(|&is_used| !is_used).unwrap();
+        node_colors.set(node_index, color);
     }
 
-    None
+    node_colors
 }
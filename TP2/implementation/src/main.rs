@@ -1,15 +1,38 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process;
 use std::time::Instant;
 
 use structopt::StructOpt;
 
 mod cli_args;
-use cli_args::{Algorithm, Cli};
+use cli_args::{
+    Algorithm, BoundsArgs, CheckArgs, Cli, Command, ExportArgs, ImportArgs, ModelFormat,
+};
+
+mod anytime;
+use anytime::Anytime;
+
+mod chromatic_bounds;
+use chromatic_bounds::compute_chromatic_bounds;
+
+mod clique;
+use clique::find_lower_bound_clique;
+
+mod colorability_model;
+use colorability_model::{load_sat_model, write_cnf, write_lp, write_mps};
+
+mod coloring;
+use coloring::Coloring;
+
+mod graph;
+use graph::{Graph, LoadedGraph};
 
 mod graph_utils;
-use graph_utils::{load_graph_from_edge_list, print_result};
+use graph_utils::{create_rng, find_coloring_errors, load_coloring, load_graph, print_result};
 
 mod greedy_algorithm;
-use greedy_algorithm::solve_with_greedy;
+use greedy_algorithm::{solve_with_constructive_algorithm, solve_with_greedy};
 
 mod branch_and_bound_algorithm;
 use branch_and_bound_algorithm::solve_with_branch_and_bound;
@@ -17,32 +40,237 @@ use branch_and_bound_algorithm::solve_with_branch_and_bound;
 mod tabu_search_algorithm;
 use tabu_search_algorithm::solve_with_tabu_search;
 
+mod evolutionary_algorithm;
+use evolutionary_algorithm::solve_with_evolutionary_algorithm;
+
+mod simulated_annealing_algorithm;
+use simulated_annealing_algorithm::solve_with_simulated_annealing;
+
 fn main() {
     // Parse args
     let args = Cli::from_args();
 
+    match &args.command {
+        Some(Command::Check(check_args)) => run_check(check_args),
+        Some(Command::Export(export_args)) => run_export(export_args),
+        Some(Command::Import(import_args)) => run_import(import_args),
+        Some(Command::Bounds(bounds_args)) => run_bounds(bounds_args),
+        None => run_solver(&args),
+    }
+}
+
+fn run_solver(args: &Cli) {
+    let (algorithm, filename) = args.solver_args();
+
     // Load graph
-    let graph = load_graph_from_edge_list(&args.filename)
-        .expect("Error parsing graph adjacency matrix from file");
+    let graph = load_graph(filename, args.format, args.representation)
+        .expect("Error parsing graph from file");
 
+    let (result, elapsed_ms, lower_bound) = match &graph {
+        LoadedGraph::Dense(graph) => solve_and_verify(graph, algorithm, args),
+        LoadedGraph::Sparse(graph) => solve_and_verify(graph, algorithm, args),
+    };
+
+    if args.show_result {
+        print_result(&result);
+    }
+
+    if args.show_exec_time {
+        println!("{}", elapsed_ms);
+    }
+
+    if let Some(lower_bound) = lower_bound {
+        let optimality = if lower_bound == result.color_count() {
+            "optimal"
+        } else {
+            "optimality not proven"
+        };
+        println!("Lower bound: {} ({})", lower_bound, optimality);
+    }
+}
+
+/// Runs the selected algorithm and checks its coloring, returning the coloring, the elapsed time in ms and the lower
+/// bound on the number of colors if requested
+fn solve_and_verify<G: Graph>(
+    graph: &G,
+    algorithm: Algorithm,
+    args: &Cli,
+) -> (Coloring, f64, Option<usize>) {
     // Start clock
     let now = Instant::now();
+    let mut anytime = match args.time_limit(algorithm) {
+        Some(time_limit) => Anytime::with_time_limit(time_limit, args.show_result),
+        None => Anytime::unlimited(),
+    };
 
     // Execute selected algorithm
-    let result = match args.algorithm {
-        Algorithm::Greedy => solve_with_greedy(&graph),
-        Algorithm::BranchAndBound => solve_with_branch_and_bound(&graph),
-        Algorithm::Tabu => solve_with_tabu_search(&graph),
+    let (result, proven_lower_bound) = match algorithm {
+        Algorithm::BranchAndBound => {
+            let (result, lower_bound) = solve_with_branch_and_bound(graph, &mut anytime);
+            (result, Some(lower_bound))
+        }
+        Algorithm::Tabu => (
+            solve_with_tabu_search(graph, &args.tabu_config(), &mut anytime),
+            None,
+        ),
+        Algorithm::Evolutionary => (
+            solve_with_evolutionary_algorithm(graph, &args.evolutionary_config(), &mut anytime),
+            None,
+        ),
+        Algorithm::SimulatedAnnealing => (
+            solve_with_simulated_annealing(graph, &args.annealing_config(), &mut anytime),
+            None,
+        ),
+        _ => (
+            solve_with_constructive_algorithm(graph, algorithm, &mut create_rng(args.seed)),
+            None,
+        ),
     };
 
     // Calculate elapsed time
     let elapsed_ms = now.elapsed().as_secs_f64() * 1000.0;
 
-    if args.show_result {
-        print_result(&result);
+    if args.verify {
+        let errors = find_coloring_errors(graph, &result, None);
+        if !errors.is_empty() {
+            eprintln!("Solver returned an improper coloring:");
+            for error in errors {
+                eprintln!("{}", error);
+            }
+            process::exit(1);
+        }
+    } else {
+        debug_assert!(
+            result.is_proper(graph),
+            "Solver returned an improper coloring"
+        );
     }
 
-    if args.show_exec_time {
-        println!("{}", elapsed_ms);
+    // Solvers that do not prove a lower bound get the size of a clique, computed outside of the measured time
+    let lower_bound = args
+        .show_bound
+        .then(|| proven_lower_bound.unwrap_or_else(|| find_lower_bound_clique(graph).len()));
+
+    (result, elapsed_ms, lower_bound)
+}
+
+fn run_check(args: &CheckArgs) {
+    let graph = load_graph(&args.filename, args.format, args.representation)
+        .expect("Error parsing graph from file");
+    let node_count = match &graph {
+        LoadedGraph::Dense(graph) => graph.node_count(),
+        LoadedGraph::Sparse(graph) => graph.node_count(),
+    };
+    let (declared_color_count, node_colors) = load_coloring(&args.coloring_filename, node_count)
+        .expect("Error parsing coloring from file");
+
+    let errors = match &graph {
+        LoadedGraph::Dense(graph) => {
+            find_coloring_errors(graph, &node_colors, Some(declared_color_count))
+        }
+        LoadedGraph::Sparse(graph) => {
+            find_coloring_errors(graph, &node_colors, Some(declared_color_count))
+        }
+    };
+    if !errors.is_empty() {
+        for error in &errors {
+            println!("{}", error);
+        }
+        println!("Invalid coloring: {} errors", errors.len());
+        process::exit(1);
+    }
+
+    println!("Valid coloring with {} colors", declared_color_count);
+}
+
+fn run_export(args: &ExportArgs) {
+    let graph = load_graph(&args.filename, args.format, args.representation)
+        .expect("Error parsing graph from file");
+
+    match &graph {
+        LoadedGraph::Dense(graph) => export_model(graph, args),
+        LoadedGraph::Sparse(graph) => export_model(graph, args),
+    }
+    .expect("Error writing model");
+}
+
+fn export_model<G: Graph>(graph: &G, args: &ExportArgs) -> io::Result<()> {
+    // One less color than the greedy coloring by default, so that a solution improves on it
+    let color_count = match args.color_count {
+        Some(color_count) => color_count,
+        None => {
+            let greedy_color_count = solve_with_greedy(graph).color_count();
+            // Graphs without edges need at most one color, which the greedy coloring already uses
+            if greedy_color_count <= 1 {
+                eprintln!(
+                    "The greedy coloring with {} colors is already optimal, there is no model to export",
+                    greedy_color_count
+                );
+                return Ok(());
+            }
+            greedy_color_count - 1
+        }
+    };
+
+    // The nodes of a clique have different colors, so fixing them breaks the symmetry between colors
+    let clique = find_lower_bound_clique(graph);
+
+    let mut writer: Box<dyn Write> = match &args.output_filename {
+        Some(output_filename) => Box::new(BufWriter::new(File::create(output_filename)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    match args.model_format {
+        ModelFormat::Cnf => write_cnf(graph, color_count, &clique, &mut writer)?,
+        ModelFormat::Lp => write_lp(graph, color_count, &clique, &mut writer)?,
+        ModelFormat::Mps => write_mps(graph, color_count, &clique, &mut writer)?,
+    }
+    writer.flush()
+}
+
+fn run_import(args: &ImportArgs) {
+    let graph = load_graph(&args.filename, args.format, args.representation)
+        .expect("Error parsing graph from file");
+
+    let (mut node_colors, errors) = match &graph {
+        LoadedGraph::Dense(graph) => import_model(graph, args),
+        LoadedGraph::Sparse(graph) => import_model(graph, args),
+    };
+    if !errors.is_empty() {
+        eprintln!("SAT model is not a proper coloring:");
+        for error in errors {
+            eprintln!("{}", error);
+        }
+        process::exit(1);
+    }
+
+    // Renumber colors in case the model does not use every color
+    node_colors.normalize();
+    print_result(&node_colors);
+}
+
+/// Reads the coloring of a SAT model, along with its errors
+fn import_model<G: Graph>(graph: &G, args: &ImportArgs) -> (Coloring, Vec<String>) {
+    let node_colors = load_sat_model(&args.model_filename, graph.node_count(), args.color_count)
+        .unwrap_or_else(|error| {
+            eprintln!("Error reading SAT model: {}", error);
+            process::exit(1);
+        });
+    let errors = find_coloring_errors(graph, &node_colors, None);
+    (node_colors, errors)
+}
+
+fn run_bounds(args: &BoundsArgs) {
+    let graph = load_graph(&args.filename, args.format, args.representation)
+        .expect("Error parsing graph from file");
+
+    let bounds = match &graph {
+        LoadedGraph::Dense(graph) => compute_chromatic_bounds(graph),
+        LoadedGraph::Sparse(graph) => compute_chromatic_bounds(graph),
+    };
+
+    if args.json {
+        println!("{}", bounds.format_json());
+    } else {
+        println!("{}", bounds.format_text());
     }
 }
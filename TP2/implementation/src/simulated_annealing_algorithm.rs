@@ -0,0 +1,308 @@
+use rand::Rng;
+
+use crate::anytime::Anytime;
+use crate::cli_args::{Algorithm, CoolingSchedule};
+use crate::coloring::Coloring;
+use crate::graph::{Graph, NodeIndex};
+use crate::graph_utils::create_rng;
+use crate::greedy_algorithm::solve_with_constructive_algorithm;
+
+/// Default number of moves at each temperature per node
+const MOVES_PER_NODE: usize = 16;
+
+/// Lambda of the adaptive cooling schedule, where a larger value lowers the temperature faster
+const ADAPTIVE_COOLING_LAMBDA: f64 = 0.7;
+
+/// Acceptance rate of the moves changing the objective at a temperature, below which the temperature counts as frozen
+const MIN_ACCEPTANCE_RATE: f64 = 0.02;
+
+/// Number of consecutive frozen temperatures without a better coloring, after which the annealing reheats or stops
+const FROZEN_TEMPERATURE_COUNT: usize = 5;
+
+/// Number of random moves used to estimate the initial temperature
+const TEMPERATURE_SAMPLE_COUNT: usize = 100;
+
+/// Parameters of simulated annealing
+#[derive(Clone, Debug)]
+pub struct AnnealingConfig {
+    /// Constructive algorithm giving the initial solution
+    pub initial_algorithm: Algorithm,
+    pub cooling_schedule: CoolingSchedule,
+    /// Factor by which the geometric cooling schedule multiplies the temperature
+    pub cooling_rate: f64,
+    /// Estimated so that half of the average worsening moves are accepted if not set
+    pub initial_temperature: Option<f64>,
+    /// MOVES_PER_NODE times the node count if not set
+    pub moves_per_temperature: Option<usize>,
+    /// Times the annealing goes back to its initial temperature once frozen, before it stops
+    pub max_reheat_count: usize,
+    /// Seed of the random number generator, drawn from the OS if not set
+    pub seed: Option<u64>,
+}
+
+/// Proper coloring explored by Kempe chain interchanges, with the size of each color class
+struct AnnealingState {
+    node_colors: Coloring,
+    class_sizes: Vec<usize>,
+    /// Number of non-empty color classes
+    color_count: usize,
+    /// Sum of the squared color class sizes, which is maximized
+    objective: i64,
+    /// Nodes of the last Kempe chain found
+    chain: Vec<NodeIndex>,
+    /// Nodes visited by the search of the Kempe chain of each stamp
+    visit_stamps: Vec<usize>,
+    current_stamp: usize,
+}
+
+impl AnnealingState {
+    fn new(node_colors: Coloring) -> Self {
+        let mut class_sizes = vec![0; node_colors.color_count()];
+        for (_, color) in node_colors.iter() {
+            class_sizes[color.unwrap()] += 1;
+        }
+
+        AnnealingState {
+            color_count: class_sizes.iter().filter(|&&size| size > 0).count(),
+            objective: class_sizes.iter().map(|&size| (size * size) as i64).sum(),
+            chain: Vec::new(),
+            visit_stamps: vec![0; node_colors.node_count()],
+            current_stamp: 0,
+            node_colors,
+            class_sizes,
+        }
+    }
+
+    /// Finds the Kempe chain of a node and another color, which is the connected component of the node in the
+    /// subgraph of the nodes with its color or the other one, and returns the change of objective when swapping its
+    /// two colors
+    fn find_kempe_chain<G: Graph>(
+        &mut self,
+        graph: &G,
+        node_index: NodeIndex,
+        other_color: usize,
+    ) -> i64 {
+        let node_color = self.node_colors.get(node_index).unwrap();
+        self.current_stamp += 1;
+        self.chain.clear();
+
+        self.chain.push(node_index);
+        self.visit_stamps[node_index.index()] = self.current_stamp;
+        let mut node_color_count = 0i64;
+        let mut i = 0;
+        while i < self.chain.len() {
+            let chain_node_index = self.chain[i];
+            let chain_node_color = self.node_colors.get(chain_node_index).unwrap();
+            if chain_node_color == node_color {
+                node_color_count += 1;
+            }
+
+            // The coloring is proper, so the neighbors in the chain have the other color of the two
+            let neighbor_color = if chain_node_color == node_color {
+                other_color
+            } else {
+                node_color
+            };
+            for neighbor_node_index in graph.neighbors(chain_node_index) {
+                if self.visit_stamps[neighbor_node_index.index()] != self.current_stamp
+                    && self.node_colors.get(neighbor_node_index) == Some(neighbor_color)
+                {
+                    self.visit_stamps[neighbor_node_index.index()] = self.current_stamp;
+                    self.chain.push(neighbor_node_index);
+                }
+            }
+            i += 1;
+        }
+        let other_color_count = self.chain.len() as i64 - node_color_count;
+
+        let node_class_size = self.class_sizes[node_color] as i64;
+        let other_class_size = self.class_sizes[other_color] as i64;
+        let new_node_class_size = node_class_size - node_color_count + other_color_count;
+        let new_other_class_size = other_class_size - other_color_count + node_color_count;
+
+        new_node_class_size * new_node_class_size + new_other_class_size * new_other_class_size
+            - node_class_size * node_class_size
+            - other_class_size * other_class_size
+    }
+
+    /// Swaps the two colors of the last Kempe chain found
+    fn swap_kempe_chain(&mut self, node_color: usize, other_color: usize, objective_delta: i64) {
+        let non_empty_class_count = |class_sizes: &[usize]| {
+            [node_color, other_color]
+                .iter()
+                .filter(|&&color| class_sizes[color] > 0)
+                .count()
+        };
+        let previous_non_empty_class_count = non_empty_class_count(&self.class_sizes);
+
+        for &chain_node_index in &self.chain {
+            let (previous_color, new_color) =
+                if self.node_colors.get(chain_node_index) == Some(node_color) {
+                    (node_color, other_color)
+                } else {
+                    (other_color, node_color)
+                };
+            self.node_colors.set(chain_node_index, new_color);
+            self.class_sizes[previous_color] -= 1;
+            self.class_sizes[new_color] += 1;
+        }
+
+        self.color_count = self.color_count + non_empty_class_count(&self.class_sizes)
+            - previous_non_empty_class_count;
+        self.objective += objective_delta;
+    }
+
+    /// Random node and color other than its own, among the colors of the initial coloring
+    fn choose_move<R: Rng>(&self, rng: &mut R) -> (NodeIndex, usize) {
+        let node_index = NodeIndex::new(rng.gen_range(0..self.node_colors.node_count()));
+        let node_color = self.node_colors.get(node_index).unwrap();
+        let mut other_color = rng.gen_range(0..self.class_sizes.len() - 1);
+        if other_color >= node_color {
+            other_color += 1;
+        }
+        (node_index, other_color)
+    }
+}
+
+/// Simulated annealing on proper colorings (Johnson et al.), where each move swaps the two colors of a random Kempe
+/// chain, and the sum of the squared color class sizes is maximized, which favors emptying small classes
+pub fn solve_with_simulated_annealing<G: Graph>(
+    graph: &G,
+    config: &AnnealingConfig,
+    anytime: &mut Anytime,
+) -> Coloring {
+    let mut rng = create_rng(config.seed);
+
+    // Get initial best solution using a constructive algorithm
+    let mut best_node_colors =
+        solve_with_constructive_algorithm(graph, config.initial_algorithm, &mut rng);
+    anytime.report(&best_node_colors);
+
+    // Kempe chains need two colors
+    if best_node_colors.color_count() < 2 {
+        return best_node_colors;
+    }
+
+    let mut state = AnnealingState::new(best_node_colors.clone());
+    let mut best_color_count = state.color_count;
+
+    let initial_temperature = config
+        .initial_temperature
+        .unwrap_or_else(|| estimate_initial_temperature(graph, &mut state, &mut rng));
+    let moves_per_temperature = config
+        .moves_per_temperature
+        .unwrap_or(MOVES_PER_NODE * graph.node_count());
+
+    let mut temperature = initial_temperature;
+    let mut frozen_temperature_count = 0usize;
+    let mut reheat_count = 0usize;
+
+    while !anytime.should_stop() {
+        let mut changing_move_count = 0usize;
+        let mut accepted_move_count = 0usize;
+        let mut has_found_better_coloring = false;
+
+        // Mean and variance of the objective at this temperature, for the adaptive cooling schedule
+        let mut objective_sum = 0f64;
+        let mut objective_square_sum = 0f64;
+
+        for _ in 0..moves_per_temperature {
+            if anytime.should_stop() {
+                break;
+            }
+
+            let (node_index, other_color) = state.choose_move(&mut rng);
+            let node_color = state.node_colors.get(node_index).unwrap();
+            let objective_delta = state.find_kempe_chain(graph, node_index, other_color);
+
+            if objective_delta != 0 {
+                changing_move_count += 1;
+            }
+
+            // Always accept moves that do not decrease the objective, and others with a probability decreasing with
+            // the temperature
+            let is_accepted = objective_delta >= 0
+                || rng.gen::<f64>() < (objective_delta as f64 / temperature).exp();
+            if is_accepted {
+                state.swap_kempe_chain(node_color, other_color, objective_delta);
+                if objective_delta != 0 {
+                    accepted_move_count += 1;
+                }
+
+                if state.color_count < best_color_count {
+                    best_color_count = state.color_count;
+                    best_node_colors = state.node_colors.clone();
+                    best_node_colors.normalize();
+                    anytime.report(&best_node_colors);
+                    has_found_better_coloring = true;
+                }
+            }
+
+            objective_sum += state.objective as f64;
+            objective_square_sum += (state.objective as f64).powi(2);
+        }
+
+        // Reheat, or stop if there are no reheats left, when frozen for a few temperatures
+        let acceptance_rate = accepted_move_count as f64 / changing_move_count.max(1) as f64;
+        if has_found_better_coloring || acceptance_rate >= MIN_ACCEPTANCE_RATE {
+            frozen_temperature_count = 0;
+        } else {
+            frozen_temperature_count += 1;
+        }
+
+        if frozen_temperature_count >= FROZEN_TEMPERATURE_COUNT {
+            if reheat_count >= config.max_reheat_count {
+                break;
+            }
+            reheat_count += 1;
+            frozen_temperature_count = 0;
+            temperature = initial_temperature;
+            continue;
+        }
+
+        temperature = match config.cooling_schedule {
+            CoolingSchedule::Geometric => temperature * config.cooling_rate,
+            CoolingSchedule::Adaptive => {
+                // Huang et al.: the temperature decreases less when the objective varies a lot
+                let move_count = moves_per_temperature.max(1) as f64;
+                let objective_mean = objective_sum / move_count;
+                let objective_variance =
+                    (objective_square_sum / move_count - objective_mean * objective_mean).max(0.0);
+                if objective_variance > 0.0 {
+                    temperature
+                        * (-ADAPTIVE_COOLING_LAMBDA * temperature / objective_variance.sqrt()).exp()
+                } else {
+                    temperature * config.cooling_rate
+                }
+            }
+        };
+    }
+
+    best_node_colors
+}
+
+/// Temperature at which a worsening move of average size is accepted with a probability of one half, estimated on
+/// random moves
+fn estimate_initial_temperature<G: Graph, R: Rng>(
+    graph: &G,
+    state: &mut AnnealingState,
+    rng: &mut R,
+) -> f64 {
+    let mut worsening_delta_sum = 0i64;
+    let mut worsening_move_count = 0usize;
+
+    for _ in 0..TEMPERATURE_SAMPLE_COUNT {
+        let (node_index, other_color) = state.choose_move(rng);
+        let objective_delta = state.find_kempe_chain(graph, node_index, other_color);
+        if objective_delta < 0 {
+            worsening_delta_sum -= objective_delta;
+            worsening_move_count += 1;
+        }
+    }
+
+    if worsening_move_count == 0 {
+        return 1.0;
+    }
+
+    worsening_delta_sum as f64 / worsening_move_count as f64 / 2f64.ln()
+}
@@ -1,57 +1,87 @@
-use std::collections::{HashMap, HashSet};
-
-use petgraph::matrix_graph::{NodeIndex, UnMatrix};
-use petgraph::visit::IntoEdgeReferences;
 use rand::Rng;
 
-use crate::graph_utils::count_colors;
-use crate::greedy_algorithm::solve_with_greedy;
+use crate::anytime::Anytime;
+use crate::cli_args::Algorithm;
+use crate::coloring::Coloring;
+use crate::graph::{Graph, NodeIndex};
+use crate::graph_utils::create_rng;
+use crate::greedy_algorithm::solve_with_constructive_algorithm;
+
+/// Parameters of the tabu search
+#[derive(Clone, Debug)]
+pub struct TabuConfig {
+    /// Constructive algorithm giving the initial solution
+    pub initial_algorithm: Algorithm,
+    /// Iterations of each tabu search, after which it fails
+    pub max_iteration_count: usize,
+    /// Tabu tenure added per conflicting edge after a move
+    pub tenure_alpha: f64,
+    /// Largest random tabu tenure, which is drawn between 1 and this value after a move
+    pub tenure_random_max: usize,
+    /// Seed of the random number generator, drawn from the OS if not set
+    pub seed: Option<u64>,
+}
 
-const MAX_TABU_ITERATION_COUNT: usize = 128;
-const ALPHA: usize = 2;
-const G: usize = 10;
+impl TabuConfig {
+    /// Number of iterations during which a node cannot go back to its previous color
+    fn tenure<R: Rng>(&self, conflict_count: usize, rng: &mut R) -> usize {
+        (self.tenure_alpha * conflict_count as f64) as usize
+            + rng.gen_range(1..=self.tenure_random_max.max(1))
+    }
+}
 
-pub fn solve_with_tabu_search(graph: &UnMatrix<(), ()>) -> HashMap<NodeIndex, usize> {
-    // Get initial best solution using greedy algorithm
-    let mut best_node_colors = solve_with_greedy(&graph);
+pub fn solve_with_tabu_search<G: Graph>(
+    graph: &G,
+    config: &TabuConfig,
+    anytime: &mut Anytime,
+) -> Coloring {
+    let mut rng = create_rng(config.seed);
 
-    // Reduce colors until no longer possible
-    loop {
+    // Get initial best solution using a constructive algorithm
+    let mut best_node_colors =
+        solve_with_constructive_algorithm(graph, config.initial_algorithm, &mut rng);
+    anytime.report(&best_node_colors);
+
+    // Reduce colors until no longer possible, a graph with nodes needing at least one color
+    while best_node_colors.color_count() > 1 {
         // Reassign smaller color with minimum conflicts to nodes with max color
         let reduced_node_colors = reduce_node_colors(graph, &best_node_colors);
 
         // Fix conflicts with tabu search until there are no more conflicts or if max iterations have been exhausted
-        let tabu_search_result = fix_conflicts_with_tabu_search(graph, &reduced_node_colors);
+        let tabu_search_result =
+            fix_conflicts_with_tabu_search(graph, &reduced_node_colors, config, &mut rng, anytime);
         match tabu_search_result {
-            Some(resolved_reduced_node_colors) => best_node_colors = resolved_reduced_node_colors,
-            None => break, // If tabu search failed, the best solution is the previous tabu search's result
+            Some(mut resolved_reduced_node_colors) => {
+                // Renumber colors in case tabu search emptied a color class
+                resolved_reduced_node_colors.normalize();
+                best_node_colors = resolved_reduced_node_colors;
+                anytime.report(&best_node_colors);
+            }
+            None => break, // If tabu search failed or was stopped, the best solution is the previous tabu search's result
         }
     }
 
     best_node_colors
 }
 
-fn reduce_node_colors(
-    graph: &UnMatrix<(), ()>,
-    node_colors: &HashMap<NodeIndex, usize>,
-) -> HashMap<NodeIndex, usize> {
-    let color_count = count_colors(node_colors);
+fn reduce_node_colors<G: Graph>(graph: &G, node_colors: &Coloring) -> Coloring {
+    let color_count = node_colors.color_count();
 
     let mut reduced_node_colors = node_colors.clone();
 
-    for (node_index, color) in node_colors {
+    for (node_index, color) in node_colors.iter() {
         // Only reassign color for nodes with the current max color
-        if *color != color_count - 1 {
+        if color != Some(color_count - 1) {
             continue;
         }
 
         // Find new node color minimizing conflicts with neighbors
         let mut best_new_color = 0usize;
-        let mut min_conflict_count = graph.neighbors(*node_index).count();
+        let mut min_conflict_count = graph.degree(node_index);
 
         for new_color in 0..color_count - 1 {
             let conflict_count =
-                count_conflicts_for_node_color(graph, *node_index, new_color, node_colors);
+                count_conflicts_for_node_color(graph, node_index, new_color, node_colors);
 
             if conflict_count < min_conflict_count {
                 best_new_color = new_color;
@@ -60,148 +90,308 @@ fn reduce_node_colors(
         }
 
         // Update node with new color
-        reduced_node_colors.insert(*node_index, best_new_color);
+        reduced_node_colors.set(node_index, best_new_color);
     }
 
     reduced_node_colors
 }
 
-fn fix_conflicts_with_tabu_search(
-    graph: &UnMatrix<(), ()>,
-    node_colors: &HashMap<NodeIndex, usize>,
-) -> Option<HashMap<NodeIndex, usize>> {
-    // Tabu list
-    let mut tabu_list = HashSet::new();
-    let mut tabu_expiration_ticks = HashMap::<usize, Vec<(NodeIndex, usize)>>::new();
-    let mut current_tick = 0usize;
+fn fix_conflicts_with_tabu_search<G: Graph, R: Rng>(
+    graph: &G,
+    node_colors: &Coloring,
+    config: &TabuConfig,
+    rng: &mut R,
+    anytime: &Anytime,
+) -> Option<Coloring> {
+    let (best_node_colors, best_conflict_count) = run_tabucol(
+        graph,
+        node_colors,
+        node_colors.color_count(),
+        config,
+        rng,
+        anytime,
+    );
+
+    // Return None if the max number of iterations has been reached without removing every conflict (tabu search
+    // failed)
+    if best_conflict_count == 0 {
+        Some(best_node_colors)
+    } else {
+        None
+    }
+}
 
-    // Current node color combination
+/// TabuCol: moves conflicting nodes to other colors among color_count colors, until there are no more conflicts, the
+/// max number of iterations has been reached or the search is stopped. Returns the coloring with the fewest conflicting
+/// edges found, along with their number.
+pub fn run_tabucol<G: Graph, R: Rng>(
+    graph: &G,
+    node_colors: &Coloring,
+    color_count: usize,
+    config: &TabuConfig,
+    rng: &mut R,
+    anytime: &Anytime,
+) -> (Coloring, usize) {
+    // Current node color combination, with its conflicts
     let mut current_node_colors = node_colors.clone();
+    let mut conflict_table = ConflictTable::new(graph, &current_node_colors, color_count);
 
-    // Count total number of conflicts in current node color combination
-    let mut current_conflict_count = 0usize;
-    for (source_node_index, target_node_index, _) in graph.edge_references() {
-        let source_node_color = current_node_colors.get(&source_node_index).unwrap();
-        let target_node_color = current_node_colors.get(&target_node_index).unwrap();
-        if source_node_color == target_node_color {
-            current_conflict_count += 1;
-        }
-    }
+    let mut best_node_colors = current_node_colors.clone();
+    let mut best_conflict_count = conflict_table.conflict_count;
+
+    // Tabu list, as the tick until which each node cannot go back to each color
+    let mut tabu_expiration_ticks = vec![0usize; graph.node_count() * color_count];
+    let mut current_tick = 0usize;
 
     // Tabu search
-    while current_tick < MAX_TABU_ITERATION_COUNT {
-        // Generate neighbors
-        let neighboring_node_color_tuples =
-            generate_neighboring_node_colors(&current_node_colors, &tabu_list);
-
-        // Find neighboring node color minimizing conflicts
-        let mut best_neighbor_node_color_tuple = neighboring_node_color_tuples[0];
-        let mut best_neighbor_conflict_count = graph.node_count();
-
-        for node_color_tuple in neighboring_node_color_tuples {
-            // Subtract conflicts caused by current node color to be replaced
-            let conflicts_for_current_node_color = count_conflicts_for_node_color(
-                graph,
-                node_color_tuple.0,
-                *current_node_colors.get(&node_color_tuple.0).unwrap(),
-                &current_node_colors,
-            );
+    while current_tick < config.max_iteration_count
+        && best_conflict_count > 0
+        && !anytime.should_stop()
+    {
+        // Find move of a conflicting node to a non tabu color minimizing conflicts, where a tabu move is allowed if it
+        // gives fewer conflicts than the best coloring (aspiration)
+        let mut best_move = None;
+        let mut best_conflict_delta = 0isize;
+
+        for &node_index in &conflict_table.conflicting_nodes {
+            let node_color = current_node_colors.get(node_index).unwrap();
+            let conflicts_for_current_node_color = conflict_table.get(node_index, node_color);
+
+            for color in 0..color_count {
+                if color == node_color {
+                    continue;
+                }
+
+                let conflict_delta = conflict_table.get(node_index, color) as isize
+                    - conflicts_for_current_node_color as isize;
+                let is_tabu =
+                    tabu_expiration_ticks[node_index.index() * color_count + color] > current_tick;
+                let is_aspirated = (conflict_table.conflict_count as isize + conflict_delta)
+                    < best_conflict_count as isize;
+                if is_tabu && !is_aspirated {
+                    continue;
+                }
+
+                if best_move.is_none() || conflict_delta < best_conflict_delta {
+                    best_move = Some((node_index, color));
+                    best_conflict_delta = conflict_delta;
+                }
+            }
+        }
 
-            // Add conflicts caused by new neighbor node color
-            let conflicts_for_neighbor_node_color = count_conflicts_for_node_color(
-                graph,
-                node_color_tuple.0,
-                node_color_tuple.1,
-                &current_node_colors,
-            );
+        // Update current node color combination with best move, and forbid moving the node back to its color
+        if let Some((node_index, color)) = best_move {
+            let previous_color = current_node_colors.get(node_index).unwrap();
+            conflict_table.move_node(graph, &mut current_node_colors, node_index, color);
 
-            let conflict_count = current_conflict_count - conflicts_for_current_node_color
-                + conflicts_for_neighbor_node_color;
+            tabu_expiration_ticks[node_index.index() * color_count + previous_color] =
+                current_tick + config.tenure(conflict_table.conflict_count, rng);
 
-            if conflict_count < best_neighbor_conflict_count {
-                best_neighbor_node_color_tuple = node_color_tuple;
-                best_neighbor_conflict_count = conflict_count;
+            if conflict_table.conflict_count < best_conflict_count {
+                best_node_colors = current_node_colors.clone();
+                best_conflict_count = conflict_table.conflict_count;
             }
         }
 
-        // Update current node color combination with best neighbor node color tuple
-        current_node_colors.insert(
-            best_neighbor_node_color_tuple.0,
-            best_neighbor_node_color_tuple.1,
-        );
-        current_conflict_count = best_neighbor_conflict_count;
-
-        // Update tabu list
-        tabu_list.insert(best_neighbor_node_color_tuple);
+        current_tick += 1;
+    }
 
-        let expiration_tick = current_tick
-            + ALPHA * best_neighbor_conflict_count
-            + rand::thread_rng().gen_range(1..G);
-        tabu_expiration_ticks
-            .entry(expiration_tick)
-            .or_default()
-            .push(best_neighbor_node_color_tuple);
+    (best_node_colors, best_conflict_count)
+}
 
-        current_tick += 1;
+/// Incrementally maintained number of neighbors of each node with each color, along with the conflicting nodes
+struct ConflictTable {
+    color_count: usize,
+    /// Number of neighbors of node i with color c at index i * color_count + c
+    neighbor_color_counts: Vec<usize>,
+    /// Nodes having a neighbor with the same color, in no particular order
+    conflicting_nodes: Vec<NodeIndex>,
+    /// Position of each node in conflicting_nodes
+    conflicting_node_positions: Vec<Option<usize>>,
+    /// Number of edges whose two nodes have the same color
+    conflict_count: usize,
+}
 
-        // Remove expired tabu list entries
-        if let Some(expired_node_color_tuples) = tabu_expiration_ticks.remove(&current_tick) {
-            for expired_node_color_tuple in expired_node_color_tuples {
-                tabu_list.remove(&expired_node_color_tuple);
+impl ConflictTable {
+    fn new<G: Graph>(graph: &G, node_colors: &Coloring, color_count: usize) -> Self {
+        let mut conflict_table = ConflictTable {
+            color_count,
+            neighbor_color_counts: vec![0; graph.node_count() * color_count],
+            conflicting_nodes: Vec::new(),
+            conflicting_node_positions: vec![None; graph.node_count()],
+            conflict_count: 0,
+        };
+
+        for (node_index, color) in node_colors.iter() {
+            let color = color.unwrap();
+            for neighbor_node_index in graph.neighbors(node_index) {
+                conflict_table.neighbor_color_counts
+                    [neighbor_node_index.index() * color_count + color] += 1;
             }
         }
 
-        // Stop tabu search and return new node color combinations when there are no more conflicts
-        if best_neighbor_conflict_count == 0 {
-            return Some(current_node_colors);
+        for (node_index, color) in node_colors.iter() {
+            let conflicts = conflict_table.get(node_index, color.unwrap());
+            conflict_table.conflict_count += conflicts;
+            conflict_table.update_conflicting_node(node_index, conflicts > 0);
         }
-    }
 
-    // Return None if the max number of iterations has been reached (tabu search failed)
-    None
-}
+        // Each conflicting edge was counted from both of its nodes
+        conflict_table.conflict_count /= 2;
 
-fn generate_neighboring_node_colors(
-    node_colors: &HashMap<NodeIndex, usize>,
-    tabu_list: &HashSet<(NodeIndex, usize)>,
-) -> Vec<(NodeIndex, usize)> {
-    let color_count = count_colors(node_colors);
+        conflict_table
+    }
 
-    let mut neighboring_node_colors = Vec::new();
+    fn get(&self, node_index: NodeIndex, color: usize) -> usize {
+        self.neighbor_color_counts[node_index.index() * self.color_count + color]
+    }
 
-    for node_index in node_colors.keys() {
-        for color in 0..color_count {
-            // Exclude current color
-            if color == *node_colors.get(node_index).unwrap() {
-                continue;
-            }
+    /// Changes the color of a node, updating the conflicts of its neighbors in O(deg)
+    fn move_node<G: Graph>(
+        &mut self,
+        graph: &G,
+        node_colors: &mut Coloring,
+        node_index: NodeIndex,
+        new_color: usize,
+    ) {
+        let previous_color = node_colors.get(node_index).unwrap();
+        self.conflict_count = self.conflict_count + self.get(node_index, new_color)
+            - self.get(node_index, previous_color);
+        node_colors.set(node_index, new_color);
+
+        for neighbor_node_index in graph.neighbors(node_index) {
+            let i = neighbor_node_index.index() * self.color_count;
+            self.neighbor_color_counts[i + previous_color] -= 1;
+            self.neighbor_color_counts[i + new_color] += 1;
+
+            let neighbor_color = node_colors.get(neighbor_node_index).unwrap();
+            let is_neighbor_conflicting = self.get(neighbor_node_index, neighbor_color) > 0;
+            self.update_conflicting_node(neighbor_node_index, is_neighbor_conflicting);
+        }
 
-            let node_color_tuple = (*node_index, color);
+        let is_node_conflicting = self.get(node_index, new_color) > 0;
+        self.update_conflicting_node(node_index, is_node_conflicting);
+    }
 
-            // Exclude color if in tabu list
-            if tabu_list.contains(&node_color_tuple) {
-                continue;
+    fn update_conflicting_node(&mut self, node_index: NodeIndex, is_conflicting: bool) {
+        let position = self.conflicting_node_positions[node_index.index()];
+        match (position, is_conflicting) {
+            (None, true) => {
+                self.conflicting_node_positions[node_index.index()] =
+                    Some(self.conflicting_nodes.len());
+                self.conflicting_nodes.push(node_index);
             }
-
-            neighboring_node_colors.push(node_color_tuple);
+            (Some(position), false) => {
+                self.conflicting_nodes.swap_remove(position);
+                if let Some(&moved_node_index) = self.conflicting_nodes.get(position) {
+                    self.conflicting_node_positions[moved_node_index.index()] = Some(position);
+                }
+                self.conflicting_node_positions[node_index.index()] = None;
+            }
+            _ => {}
         }
     }
-
-    neighboring_node_colors
 }
 
-fn count_conflicts_for_node_color(
-    graph: &UnMatrix<(), ()>,
+fn count_conflicts_for_node_color<G: Graph>(
+    graph: &G,
     node_index: NodeIndex,
     new_color: usize,
-    existing_node_colors: &HashMap<NodeIndex, usize>,
+    existing_node_colors: &Coloring,
 ) -> usize {
     let mut conflict_count = 0usize;
     for neighbor_node_index in graph.neighbors(node_index) {
-        let neighbor_color = *existing_node_colors.get(&neighbor_node_index).unwrap();
+        let neighbor_color = existing_node_colors.get(neighbor_node_index).unwrap();
         if new_color == neighbor_color {
             conflict_count += 1;
         }
     }
     conflict_count
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::SparseGraph;
+
+    fn coloring_from_colors(colors: &[usize]) -> Coloring {
+        let mut node_colors = Coloring::new(colors.len());
+        for (i, &color) in colors.iter().enumerate() {
+            node_colors.set(NodeIndex::new(i), color);
+        }
+        node_colors
+    }
+
+    /// Checks the incrementally updated table against a table built from scratch
+    fn assert_matches_rebuilt_table<G: Graph>(
+        conflict_table: &ConflictTable,
+        graph: &G,
+        node_colors: &Coloring,
+    ) {
+        let rebuilt_table = ConflictTable::new(graph, node_colors, conflict_table.color_count);
+        assert_eq!(
+            conflict_table.neighbor_color_counts,
+            rebuilt_table.neighbor_color_counts
+        );
+        assert_eq!(conflict_table.conflict_count, rebuilt_table.conflict_count);
+        assert_eq!(
+            conflict_table.conflict_count,
+            node_colors.conflicting_edges(graph).len()
+        );
+
+        let mut conflicting_nodes = conflict_table.conflicting_nodes.clone();
+        let mut rebuilt_conflicting_nodes = rebuilt_table.conflicting_nodes.clone();
+        conflicting_nodes.sort_unstable();
+        rebuilt_conflicting_nodes.sort_unstable();
+        assert_eq!(conflicting_nodes, rebuilt_conflicting_nodes);
+
+        for (position, node_index) in conflict_table.conflicting_nodes.iter().enumerate() {
+            assert_eq!(
+                conflict_table.conflicting_node_positions[node_index.index()],
+                Some(position)
+            );
+        }
+    }
+
+    #[test]
+    fn move_node_updates_conflicts_of_triangle() {
+        let graph = SparseGraph::from_edges(4, &[(0, 1), (1, 2), (0, 2), (2, 3)]);
+        let mut node_colors = coloring_from_colors(&[0, 0, 0, 1]);
+        let mut conflict_table = ConflictTable::new(&graph, &node_colors, 2);
+        assert_eq!(conflict_table.conflict_count, 3);
+
+        conflict_table.move_node(&graph, &mut node_colors, NodeIndex::new(0), 1);
+        assert_eq!(conflict_table.conflict_count, 1);
+        assert_matches_rebuilt_table(&conflict_table, &graph, &node_colors);
+
+        conflict_table.move_node(&graph, &mut node_colors, NodeIndex::new(2), 1);
+        assert_eq!(conflict_table.conflict_count, 2);
+        assert_matches_rebuilt_table(&conflict_table, &graph, &node_colors);
+    }
+
+    #[test]
+    fn move_node_matches_rebuilt_table() {
+        let node_count = 12;
+        let color_count = 3;
+        let mut rng = create_rng(Some(8775));
+        let edges: Vec<_> = (0..node_count)
+            .flat_map(|i| (i + 1..node_count).map(move |j| (i, j)))
+            .filter(|_| rng.gen_bool(0.4))
+            .collect();
+        let graph = SparseGraph::from_edges(node_count, &edges);
+
+        let colors: Vec<_> = (0..node_count)
+            .map(|_| rng.gen_range(0..color_count))
+            .collect();
+        let mut node_colors = coloring_from_colors(&colors);
+        let mut conflict_table = ConflictTable::new(&graph, &node_colors, color_count);
+        assert_matches_rebuilt_table(&conflict_table, &graph, &node_colors);
+
+        for _ in 0..200 {
+            let node_index = NodeIndex::new(rng.gen_range(0..node_count));
+            let new_color = rng.gen_range(0..color_count);
+            conflict_table.move_node(&graph, &mut node_colors, node_index, new_color);
+            assert_matches_rebuilt_table(&conflict_table, &graph, &node_colors);
+        }
+    }
+}